@@ -0,0 +1,87 @@
+use std::collections::VecDeque;
+
+use crate::app::GameResult;
+
+// Only the last few games count, so the computer reacts when the user improves or has a bad run.
+const RECENT_GAMES: usize = 6;
+const STARTING_MISTAKE_PERCENTAGE: u8 = 50;
+const MISTAKE_PERCENTAGE_STEP: i32 = 10;
+
+// Tracks the user's recent results and sets the computer's mistake rate from them after every game,
+// so that wins and losses roughly even out. Each win or loss in the window moves the rate one step
+// away from where it started, and drops out again once the game is old enough. A perfect player with
+// a mistake rate of 0% can't be beaten, while a rate of 100% plays completely at random.
+pub struct AdaptiveDifficulty {
+    recent_results: VecDeque<GameResult>,
+    mistake_percentage: u8,
+}
+
+impl AdaptiveDifficulty {
+    pub fn default() -> AdaptiveDifficulty {
+        AdaptiveDifficulty {
+            recent_results: VecDeque::with_capacity(RECENT_GAMES),
+            mistake_percentage: STARTING_MISTAKE_PERCENTAGE,
+        }
+    }
+
+    pub fn mistake_percentage(&self) -> u8 {
+        self.mistake_percentage
+    }
+
+    pub fn record_result(&mut self, result: GameResult) {
+        if self.recent_results.len() == RECENT_GAMES {
+            self.recent_results.pop_front();
+        }
+        self.recent_results.push_back(result);
+
+        // Positive when the user has been winning more than losing.
+        let balance: i32 = self
+            .recent_results
+            .iter()
+            .map(|result| match result {
+                GameResult::UserWon => 1,
+                GameResult::ComputerWon => -1,
+                GameResult::Draw => 0,
+            })
+            .sum();
+
+        let mistake_percentage =
+            STARTING_MISTAKE_PERCENTAGE as i32 - balance * MISTAKE_PERCENTAGE_STEP;
+        self.mistake_percentage = mistake_percentage.clamp(0, 100) as u8;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn play(adaptive: &mut AdaptiveDifficulty, results: &[GameResult]) {
+        for result in results {
+            adaptive.record_result(*result);
+        }
+    }
+
+    #[test]
+    fn each_result_only_counts_once() {
+        let mut adaptive = AdaptiveDifficulty::default();
+        play(&mut adaptive, &[GameResult::UserWon]);
+        assert_eq!(adaptive.mistake_percentage(), 40);
+
+        // A draw leaves the win in the window but doesn't count it again.
+        play(&mut adaptive, &[GameResult::Draw, GameResult::Draw]);
+        assert_eq!(adaptive.mistake_percentage(), 40);
+
+        play(&mut adaptive, &[GameResult::ComputerWon]);
+        assert_eq!(adaptive.mistake_percentage(), 50);
+    }
+
+    #[test]
+    fn old_results_drop_out_of_the_window() {
+        let mut adaptive = AdaptiveDifficulty::default();
+        play(&mut adaptive, &[GameResult::UserWon; RECENT_GAMES]);
+        assert_eq!(adaptive.mistake_percentage(), 0);
+
+        play(&mut adaptive, &[GameResult::Draw; RECENT_GAMES]);
+        assert_eq!(adaptive.mistake_percentage(), STARTING_MISTAKE_PERCENTAGE);
+    }
+}
@@ -3,39 +3,111 @@ use std::sync::mpsc::Sender;
 
 use rand::Rng;
 
-use crate::computer::{Character, Trigger};
+use noughts_and_crosses::board::{
+    BoardCell, BoardRules, BoardState, Move, PlaceTokenResult, Player, Position,
+};
+use noughts_and_crosses::strategy::{
+    self, Annotation, HintReason, Level, LineDirection, MoveQuality, Outcome,
+};
 
-const BOARD_ROWS: usize = 3;
-const BOARD_COLUMNS: usize = 3;
-const MAX_BOARD_ROWS_INDEX: usize = BOARD_ROWS - 1;
-const MAX_BOARD_COLUMNS_INDEX: usize = BOARD_COLUMNS - 1;
+use crate::adaptive::AdaptiveDifficulty;
+use crate::bindings::{Bindings, Command};
+use crate::cell_entry;
+use crate::computer::{Character, Trigger, TurnId};
+use crate::config::{Colours, Config};
+use crate::game_match::Match;
+use crate::network::{Message, NetworkEvent, Peer, Role};
+use crate::record::{GameRecord, RecordResult};
+use crate::replay::Replay;
+use crate::stats::Stats;
+
+const MISTAKE_PERCENTAGE_STEP: u8 = 10;
 
 pub struct App {
     pub instructions: String,
     pub chat: String,
     pub game_state: GameState,
     pub cursor_location: CursorLocation,
+    pub adaptive_difficulty: AdaptiveDifficulty,
+    // Which key does what, see bindings.rs.
+    pub bindings: Bindings,
+    pub colours: Colours,
+    // Who goes first in single games. None picks at random each game.
+    starting_player: Option<Player>,
+    // Results against each opponent and level, across every session.
+    pub stats: Stats,
+    // The score when playing a series of games instead of one at a time.
+    pub game_match: Match,
+    // Whether the board is coloured by what each empty cell is worth to the side to move.
+    pub show_analysis: bool,
+    pub analysis: Vec<((usize, usize), Outcome)>,
+    // The position the analysis was worked out for, so it's only redone after a move.
+    analysed_position: Option<String>,
+    // Every move of the last finished game, marked against the best move that could have been played.
+    pub review: Vec<Annotation>,
+    reviewed: bool,
+    // The game and turn the computer is expected to be acting on. Actions tagged with any other id
+    // are left over from a game that was reset or a turn that was undone.
+    turn_id: TurnId,
+    // The other player's connection in a network game.
+    peer: Option<Peer>,
+    // The chat message being typed, if the user is typing one.
+    pub chat_draft: Option<String>,
+    // The cell being typed in, like b3, if the user is typing one.
+    pub cell_draft: Option<String>,
+    // The saved game being watched, if the user is watching one.
+    pub replay: Option<Replay>,
 }
 
 impl App {
     pub fn default() -> App {
-        App {
-            instructions: String::from("Press S to start game."),
+        let mut app = App {
+            instructions: String::new(),
             chat: String::from(""),
             game_state: GameState {
-                current_turn: Player::User,
+                current_turn: Player::Cross,
                 winner: None,
                 board_state: BoardState::default(),
                 difficulty_level: Level::Easy,
                 started: false,
                 computer_character: Character::SpeedySteve,
+                moves: vec![],
+                undone_moves: vec![],
+                chat: vec![],
+                mode: GameMode::VsComputer,
+                players: Config::default().players,
             },
-            cursor_location: CursorLocation::default(),
-        }
+            cursor_location: CursorLocation::centre_of(&BoardState::default()),
+            adaptive_difficulty: AdaptiveDifficulty::default(),
+            bindings: Bindings::default(),
+            colours: Config::default().colours,
+            starting_player: None,
+            stats: Stats::default(),
+            game_match: Match::default(),
+            show_analysis: false,
+            analysis: vec![],
+            analysed_position: None,
+            review: vec![],
+            reviewed: false,
+            turn_id: TurnId { game: 0, turn: 0 },
+            peer: None,
+            chat_draft: None,
+            cell_draft: None,
+            replay: None,
+        };
+        app.instructions = app.start_instructions();
+        app
+    }
+
+    // The instructions name keys, so they're written again with the new ones.
+    pub fn set_bindings(&mut self, bindings: Bindings) {
+        self.bindings = bindings;
+        self.instructions = self.start_instructions();
     }
 
     // I couldnt make up my mind on how I wanted to order the cells.
     // This is what I've gone with [row][cell] starting with [0][0] being the bottom left cell.
+    // Bigger boards carry on the same way, with rows counting up the board and columns to the right.
     //    *       *       *
     // [2][0]  [2][1]  [2][2]
     //    *       *       *
@@ -46,9 +118,8 @@ impl App {
     pub fn up(&mut self) {
         let CursorLocation { row, column } = self.cursor_location;
 
-        match row {
-            MAX_BOARD_ROWS_INDEX => (),
-            _ => self.move_cursor_location(row + 1, column),
+        if row + 1 < self.game_state.board_state.rules().rows {
+            self.move_cursor_location(row + 1, column);
         }
     }
 
@@ -73,70 +144,279 @@ impl App {
     pub fn right(&mut self) {
         let CursorLocation { row, column } = self.cursor_location;
 
-        match column {
-            MAX_BOARD_COLUMNS_INDEX => (),
-            _ => self.move_cursor_location(row, column + 1),
+        if column + 1 < self.game_state.board_state.rules().columns {
+            self.move_cursor_location(row, column + 1);
         }
     }
 
     pub fn enter(&mut self, computer_sender: &Sender<Trigger>) {
         let CursorLocation { row, column } = self.cursor_location;
 
-        if self.game_state.winner != None || !self.game_state.started {
+        if self.game_state.winner.is_some() || !self.game_state.started {
             return;
         }
 
-        match self.game_state.current_turn {
-            Player::Computer => {
+        match (self.game_state.mode, self.game_state.current_turn) {
+            (GameMode::HotSeat, player) => {
+                let result = self.game_state.place_token((row, column), player);
+                self.handle_hot_seat_place_token_result(result);
+            }
+            (GameMode::Network(_), Player::Nought) => {
+                self.instructions = format!(
+                    "{}s turn. Please wait.",
+                    self.game_state.player_name(Player::Nought)
+                );
+            }
+            (GameMode::Network(_), Player::Cross) => {
+                let result = self.game_state.place_token((row, column), Player::Cross);
+
+                if let PlaceTokenResult::Success | PlaceTokenResult::SuccessWithWinner(_) = result {
+                    self.send_to_peer(Message::Move(row, column));
+                }
+
+                self.handle_network_place_token_result(result, Player::Cross);
+            }
+            (GameMode::VsComputer, Player::Nought) => {
                 self.instructions = format!(
                     "{}s turns. Please wait.",
                     self.game_state.computer_character
                 );
             }
-            Player::User => {
-                self.game_state
-                    .board_state
-                    .try_place_token((row, column), Player::User)
-                    .handle_user_place_token_result(self, &computer_sender);
+            (GameMode::VsComputer, Player::Cross) => {
+                let result = self.game_state.place_token((row, column), Player::Cross);
+                self.handle_user_place_token_result(result, computer_sender);
+            }
+        }
+    }
+
+    // Places a token in the cell the digit stands for on the 3x3 board, see cell_entry.rs.
+    pub fn place_digit(&mut self, digit: u32, computer_sender: &Sender<Trigger>) {
+        match cell_entry::cell_for_digit(
+            digit,
+            self.bindings.digit_layout,
+            self.game_state.board_state.rules(),
+        ) {
+            Some((row, column)) => {
+                self.move_cursor_location(row, column);
+                self.enter(computer_sender);
+            }
+            None => {
+                self.instructions = format!(
+                    "Digits only pick cells on the 3x3 board. Press {} to type a cell like b3 instead.",
+                    self.bindings.key_name(Command::TypeCell)
+                );
+            }
+        }
+    }
+
+    pub fn start_cell_entry(&mut self) {
+        if self.game_state.started && self.game_state.winner.is_none() {
+            self.cell_draft = Some(String::new());
+        } else {
+            self.instructions = String::from("Cells can only be typed in during a game.");
+        }
+    }
+
+    pub fn place_typed_cell(&mut self, computer_sender: &Sender<Trigger>) {
+        if let Some(text) = self.cell_draft.take() {
+            match cell_entry::parse_cell_name(&text, self.game_state.board_state.rules()) {
+                Ok((row, column)) => {
+                    self.move_cursor_location(row, column);
+                    self.enter(computer_sender);
+                }
+                Err(error) => self.instructions = format!("Couldn't place a token: {}.", error),
+            }
+        }
+    }
+
+    pub fn undo(&mut self, computer_sender: &Sender<Trigger>) {
+        if let GameMode::Network(_) = self.game_state.mode {
+            self.instructions = String::from("Moves can't be undone in a network game.");
+            return;
+        }
+
+        if !self.game_state.started || self.game_state.winner.is_some() || self.game_finished() {
+            self.instructions = String::from("Moves can only be undone while a game is in play.");
+            return;
+        }
+
+        if self.game_state.mode == GameMode::HotSeat {
+            if self.game_state.moves.is_empty() {
+                self.instructions = String::from("No tokens have been placed yet.");
+                return;
+            }
+
+            self.game_state.undo();
+            self.instructions = format!(
+                "Move undone. {} Or press {} to redo.",
+                self.turn_instructions(),
+                self.bindings.key_name(Command::Redo)
+            );
+            return;
+        }
+
+        if !self
+            .game_state
+            .moves
+            .iter()
+            .any(|played_move| played_move.player == Player::Cross)
+        {
+            self.instructions = String::from("You haven't placed a token yet.");
+            return;
+        }
+
+        // The computer may still be thinking about the move that is being undone.
+        self.next_turn_id();
+        computer_sender.send(Trigger::Cancel).unwrap();
+
+        self.game_state.undo();
+        self.instructions = format!(
+            "Move undone. Press {} to place your token or {} to redo.",
+            self.bindings.key_name(Command::Place),
+            self.bindings.key_name(Command::Redo)
+        );
+    }
+
+    pub fn redo(&mut self, computer_sender: &Sender<Trigger>) {
+        if let GameMode::Network(_) = self.game_state.mode {
+            self.instructions = String::from("Moves can't be redone in a network game.");
+            return;
+        }
+
+        if self.game_state.mode == GameMode::HotSeat {
+            if self.game_state.redo() {
+                self.instructions = format!("Move redone. {}", self.turn_instructions());
+            } else {
+                self.instructions = String::from("There are no moves to redo.");
             }
+            return;
+        }
+
+        if self.game_state.current_turn == Player::Nought {
+            self.instructions = format!(
+                "{}s turns. Please wait.",
+                self.game_state.computer_character
+            );
+            return;
+        }
+
+        if !self.game_state.redo() {
+            self.instructions = String::from("There are no moves to redo.");
+            return;
+        }
+
+        if self.game_state.current_turn == Player::Nought {
+            let turn_id = self.next_turn_id();
+            computer_sender
+                .send(Trigger::ComputersTurn(turn_id, self.game_state.clone()))
+                .unwrap();
+            self.instructions = format!("{}s turn.", self.game_state.computer_character);
+        } else {
+            self.instructions = format!("Move redone. {}", self.place_instructions());
         }
     }
 
     pub fn start_game(&mut self, computer_sender: &Sender<Trigger>) {
+        match self.game_state.mode {
+            GameMode::Network(Role::Guest) if !self.game_state.started => {
+                self.instructions = String::from("Waiting for the host to start the game.");
+                return;
+            }
+            GameMode::Network(Role::Host) if self.peer.is_none() => {
+                self.instructions = String::from("Waiting for someone to join the game.");
+                return;
+            }
+            _ => (),
+        }
+
+        if self.game_match.is_over() {
+            self.instructions = format!(
+                "The match is over. Press {} to start a new match.",
+                self.bindings.key_name(Command::NewGame)
+            );
+            return;
+        }
+
         if !self.game_state.started {
-            let players = [Player::User, Player::Computer];
-            // Generate random number in the range [0, 1]
-            let starting_player = players[rand::thread_rng().gen_range(0..2)];
+            let starting_player = if self.plays_match() {
+                self.game_match.next_starter()
+            } else if let Some(player) = self.starting_player {
+                player
+            } else {
+                let players = [Player::Cross, Player::Nought];
+                // Generate random number in the range [0, 1]
+                players[rand::thread_rng().gen_range(0..2)]
+            };
 
             self.game_state.current_turn = starting_player;
             self.game_state.started = true;
 
-            if starting_player == Player::Computer {
+            if self.game_state.mode == GameMode::HotSeat {
+                self.instructions = format!("Game started! {}", self.turn_instructions());
+            } else if let GameMode::Network(_) = self.game_state.mode {
+                let first = match starting_player {
+                    Player::Cross => Role::Host,
+                    Player::Nought => Role::Guest,
+                };
+                self.send_to_peer(Message::Start(self.game_state.board_state.rules(), first));
+                self.instructions = self.network_turn_instructions();
+            } else if starting_player == Player::Nought {
                 self.instructions = format!(
                     "Game started! {} will go first.",
                     self.game_state.computer_character
                 );
-                computer_sender.send(Trigger::ComputersTurnFirst).unwrap();
+                let turn_id = self.next_turn_id();
+                self.send_match_point(turn_id, computer_sender);
+                computer_sender
+                    .send(Trigger::ComputersTurnFirst(
+                        turn_id,
+                        self.game_state.clone(),
+                    ))
+                    .unwrap();
             } else {
-                self.instructions =
-                    String::from("Game started! Your turn first. Press enter to your place token.");
+                self.instructions = format!(
+                    "Game started! Your turn first. {}",
+                    self.place_instructions()
+                );
+                self.send_match_point(self.turn_id, computer_sender);
             }
         } else {
             self.instructions = String::from("Game is already started.")
         }
     }
 
-    pub fn new_game(&mut self) {
-        if self.game_finished() || self.game_state.winner != None {
+    pub fn new_game(&mut self, computer_sender: &Sender<Trigger>) {
+        if self.game_finished() || self.game_state.winner.is_some() {
             self.restart_game();
+            if self.game_match.is_over() {
+                self.game_match.reset();
+            }
+            // Stops the computer from finishing off anything it was still saying about the last game.
+            if self.game_state.mode == GameMode::VsComputer {
+                computer_sender.send(Trigger::Cancel).unwrap();
+            }
         } else {
             self.instructions =
                 String::from("Unable to start a new game until the current game is finished.");
         }
     }
 
+    // Sets up the first game from the config. Only called before anything has been played.
+    pub fn apply_config(&mut self, config: &Config) {
+        match config.level {
+            Some(level) => self.update_level(level),
+            None => self.use_adaptive_level(),
+        }
+        self.game_state.computer_character = config.opponent;
+        self.game_state.board_state = BoardState::new(config.board);
+        self.cursor_location = CursorLocation::centre_of(&self.game_state.board_state);
+        self.starting_player = config.first;
+        self.colours = config.colours;
+        self.game_state.players = config.players.clone();
+    }
+
     pub fn update_level(&mut self, level: Level) {
-        if self.game_finished() || self.game_state.winner != None || !self.game_state.started {
+        if self.game_finished() || self.game_state.winner.is_some() || !self.game_state.started {
             self.game_state.difficulty_level = level;
         } else {
             self.instructions =
@@ -144,362 +424,1280 @@ impl App {
         }
     }
 
+    pub fn toggle_game_mode(&mut self) {
+        if let GameMode::Network(_) = self.game_state.mode {
+            self.instructions = String::from("Unable to change the mode during a network game.");
+            return;
+        }
+
+        if self.game_finished() || self.game_state.winner.is_some() || !self.game_state.started {
+            self.game_state.mode = match self.game_state.mode {
+                GameMode::VsComputer => GameMode::HotSeat,
+                _ => GameMode::VsComputer,
+            };
+            // The score from the other mode doesn't carry over.
+            self.game_match.reset();
+        } else {
+            self.instructions =
+                String::from("Unable to change the mode while the game is being played.");
+        }
+    }
+
     pub fn swap_computer_character(&mut self) {
-        if self.game_finished() || self.game_state.winner != None || !self.game_state.started {
+        if self.game_finished() || self.game_state.winner.is_some() || !self.game_state.started {
             let new_computer_character = match self.game_state.computer_character {
                 Character::ChattyDave => Character::SpeedySteve,
                 Character::SpeedySteve => Character::ChattyDave,
             };
 
             self.game_state.computer_character = new_computer_character;
+            self.game_match.reset();
+            if let Level::Medium(_) = self.game_state.difficulty_level {
+                self.game_state.difficulty_level =
+                    Level::Medium(new_computer_character.default_mistake_percentage());
+            }
         } else {
             self.instructions =
                 String::from("Unable to change your opponent while the game is being played.");
         }
     }
 
+    // Keeps the mistake rate if Medium is already picked, otherwise starts from the opponent's.
+    pub fn use_medium_level(&mut self) {
+        let mistake_percentage = match self.game_state.difficulty_level {
+            Level::Medium(mistake_percentage) => mistake_percentage,
+            _ => self
+                .game_state
+                .computer_character
+                .default_mistake_percentage(),
+        };
+        self.update_level(Level::Medium(mistake_percentage));
+    }
+
+    pub fn increase_mistake_percentage(&mut self) {
+        self.update_mistake_percentage(|mistake_percentage| {
+            (mistake_percentage + MISTAKE_PERCENTAGE_STEP).min(100)
+        });
+    }
+
+    pub fn decrease_mistake_percentage(&mut self) {
+        self.update_mistake_percentage(|mistake_percentage| {
+            mistake_percentage.saturating_sub(MISTAKE_PERCENTAGE_STEP)
+        });
+    }
+
+    fn update_mistake_percentage(&mut self, change: fn(u8) -> u8) {
+        match self.game_state.difficulty_level {
+            Level::Medium(mistake_percentage) => {
+                self.update_level(Level::Medium(change(mistake_percentage)))
+            }
+            _ => {
+                self.instructions = String::from("The mistake rate can only be changed on Medium.");
+            }
+        }
+    }
+
+    pub fn use_adaptive_level(&mut self) {
+        self.update_level(Level::Adaptive(
+            self.adaptive_difficulty.mistake_percentage(),
+        ));
+    }
+
+    fn record_result(&mut self, result: GameResult) {
+        if self.game_state.mode != GameMode::VsComputer {
+            return;
+        }
+
+        self.stats.record(
+            self.game_state.computer_character,
+            self.game_state.difficulty_level,
+            result,
+        );
+        if let Err(error) = self.stats.save(&Stats::path()) {
+            self.instructions = format!("{} Couldn't save stats: {}", self.instructions, error);
+        }
+
+        if let Level::Adaptive(_) = self.game_state.difficulty_level {
+            self.adaptive_difficulty.record_result(result);
+            self.game_state.difficulty_level =
+                Level::Adaptive(self.adaptive_difficulty.mistake_percentage());
+        }
+    }
+
     fn game_finished(&mut self) -> bool {
-        self.game_state.board_state.cells.into_iter().all(|row| {
-            row.into_iter().all(|cell| match cell {
-                BoardCellState::NotSelected(BoardCell::Occupied(_player))
-                | BoardCellState::Selected(BoardCell::Occupied(_player)) => true,
-                _ => false,
-            })
-        })
+        self.game_state.board_state.is_full()
     }
 
-    pub fn computer_place_token(
-        &mut self,
-        (row, column): (usize, usize),
-        computer_sender: &Sender<Trigger>,
-    ) {
-        self.game_state
-            .board_state
-            .try_place_token((row, column), Player::Computer)
-            .handle_computer_place_token_result(self, &computer_sender);
+    pub fn change_board_rules(&mut self) {
+        if self.game_state.mode == GameMode::Network(Role::Guest) {
+            self.instructions = String::from("The host picks the board in a network game.");
+            return;
+        }
+
+        if self.game_finished() || self.game_state.winner.is_some() || !self.game_state.started {
+            let rules = self.game_state.board_state.rules().next_preset();
+            self.game_state.board_state = BoardState::new(rules);
+            self.restart_game();
+        } else {
+            self.instructions =
+                String::from("Unable to change the board while the game is being played.");
+        }
     }
 
-    pub fn move_cursor_location(&mut self, row: usize, column: usize) {
-        let CursorLocation {
-            row: current_row,
-            column: current_column,
-        } = self.cursor_location;
+    // Moves the cursor to the best cell for whoever's turn it is and says why it's a good move.
+    pub fn hint(&mut self) {
+        if !self.game_state.started || self.game_state.winner.is_some() || self.game_finished() {
+            self.instructions = String::from("Hints are only available during a game.");
+            return;
+        }
 
-        if let BoardCellState::Selected(selected_cell) =
-            &self.game_state.board_state.cells[current_row][current_column]
-        {
-            self.game_state.board_state.cells[current_row][current_column] =
-                BoardCellState::NotSelected(*selected_cell);
+        let player = self.game_state.current_turn;
+        if player == Player::Nought && self.game_state.mode != GameMode::HotSeat {
+            self.instructions = String::from("Wait for your turn to ask for a hint.");
+            return;
         }
-        if let BoardCellState::NotSelected(cell) = &self.game_state.board_state.cells[row][column] {
-            self.game_state.board_state.cells[row][column] = BoardCellState::Selected(*cell);
+
+        let hint = match strategy::find_hint(&self.game_state.board_state, player) {
+            Some(hint) => hint,
+            None => return,
+        };
+        let opponent = self.game_state.player_name(player.opponent());
+        let reason = match hint.reason {
+            HintReason::WinsNow => String::from("wins now"),
+            HintReason::Blocks(LineDirection::Row) => format!("blocks {}'s row", opponent),
+            HintReason::Blocks(LineDirection::Column) => format!("blocks {}'s column", opponent),
+            HintReason::Blocks(LineDirection::Diagonal) => {
+                format!("blocks {}'s diagonal", opponent)
+            }
+            HintReason::CreatesFork => {
+                String::from("creates a fork, with two ways to win next turn")
+            }
+            HintReason::Strongest => String::from("is the strongest move"),
+        };
+
+        let (row, column) = hint.cell;
+        self.move_cursor_location(row, column);
+        self.instructions = format!("Hint: this cell {}. {}", reason, self.place_instructions());
+    }
+
+    pub fn toggle_analysis(&mut self) {
+        self.show_analysis = !self.show_analysis;
+        self.instructions = if self.show_analysis {
+            String::from(
+                "Analysis on. Blue cells win, yellow draw and red lose for the side to move. \
+                 The number is how many moves until the game ends.",
+            )
+        } else {
+            String::from("Analysis off.")
+        };
+    }
+
+    // Solves the board again if it has changed since the last time. Nothing is shown outside of a
+    // game, since there's no side to move.
+    pub fn update_analysis(&mut self) {
+        let in_play =
+            self.game_state.started && self.game_state.winner.is_none() && !self.game_finished();
+        if !self.show_analysis || !in_play {
+            self.analysis.clear();
+            self.analysed_position = None;
+            return;
         }
 
-        self.cursor_location = CursorLocation {
-            row: row,
-            column: column,
+        let position = Position {
+            board_state: self.game_state.board_state.clone(),
+            to_move: self.game_state.current_turn,
         };
+        let key = position.to_string();
+        if self.analysed_position.as_ref() != Some(&key) {
+            self.analysis = strategy::solve_cells(&position.board_state, position.to_move);
+            self.analysed_position = Some(key);
+        }
     }
 
-    pub fn restart_game(&mut self) {
-        self.instructions = String::from("Press S to start game.");
-        self.game_state.board_state = BoardState::default();
-        self.cursor_location = CursorLocation::default();
-        self.game_state.winner = None;
-        self.game_state.started = false;
+    // Reviews the game once it's over. The review stays up until the next game is started.
+    pub fn update_review(&mut self) {
+        let over = self.game_state.winner.is_some() || self.game_finished();
+        if !over {
+            self.review.clear();
+            self.reviewed = false;
+            return;
+        }
+
+        if !self.reviewed {
+            self.review = strategy::annotate_moves(
+                self.game_state.board_state.rules(),
+                &self.game_state.moves,
+            );
+            self.reviewed = true;
+        }
     }
-}
 
-#[derive(Clone, Copy, Debug)]
-pub struct CursorLocation {
-    pub row: usize,
-    pub column: usize,
-}
+    // The review of the saved game being watched, otherwise of the last game played.
+    pub fn shown_review(&self) -> &[Annotation] {
+        match &self.replay {
+            Some(replay) => replay.review(),
+            None => &self.review,
+        }
+    }
+
+    // Goes to the next inaccuracy or blunder after the current move of the game being watched. If
+    // nothing is being watched, the last game played is opened as a replay.
+    pub fn jump_to_flagged_move(&mut self) {
+        let flagged_steps: Vec<usize> = self
+            .shown_review()
+            .iter()
+            .filter(|annotation| annotation.quality != MoveQuality::Best)
+            .map(|annotation| annotation.played_move.turn)
+            .collect();
+
+        if flagged_steps.is_empty() {
+            self.instructions = String::from("There are no mistakes to look at.");
+            return;
+        }
 
-impl Default for CursorLocation {
-    fn default() -> Self {
-        CursorLocation { row: 1, column: 1 }
+        let replay = self.replay.get_or_insert_with(|| {
+            Replay::with_review(
+                GameRecord::from_game_state(&self.game_state),
+                self.review.clone(),
+            )
+        });
+        let step = flagged_steps
+            .iter()
+            .copied()
+            .find(|step| *step > replay.step())
+            .unwrap_or(flagged_steps[0]);
+        replay.go_to(step);
     }
-}
 
-#[derive(Clone, Copy, Debug)]
-pub struct GameState {
-    pub board_state: BoardState,
-    pub current_turn: Player,
-    pub difficulty_level: Level,
-    pub started: bool,
-    pub computer_character: Character,
-    winner: Option<Player>,
-}
+    pub fn change_match_format(&mut self) {
+        if let GameMode::Network(_) = self.game_state.mode {
+            self.instructions = String::from("Matches can't be played in a network game.");
+            return;
+        }
 
-impl GameState {
-    pub fn swap_current_turn(&mut self) {
-        self.current_turn = match self.current_turn {
-            Player::Computer => Player::User,
-            Player::User => Player::Computer,
+        if self.game_finished() || self.game_state.winner.is_some() || !self.game_state.started {
+            self.game_match.format = self.game_match.format.next();
+            self.game_match.reset();
+        } else {
+            self.instructions =
+                String::from("Unable to change the match while the game is being played.");
+        }
+    }
+
+    fn plays_match(&self) -> bool {
+        self.game_match.is_on() && !matches!(self.game_state.mode, GameMode::Network(_))
+    }
+
+    fn send_match_point(&self, turn_id: TurnId, computer_sender: &Sender<Trigger>) {
+        if self.game_state.mode != GameMode::VsComputer {
+            return;
+        }
+
+        if let Some(match_point) = self.game_match.match_point() {
+            computer_sender
+                .send(Trigger::MatchPoint(
+                    turn_id,
+                    self.game_state.computer_character,
+                    match_point,
+                ))
+                .unwrap();
+        }
+    }
+
+    // Counts the finished game towards the match. Returns true if it ended the match, in which case
+    // the instructions are replaced with the final score.
+    fn update_match(&mut self, result: GameResult) -> bool {
+        if !self.plays_match() {
+            return false;
+        }
+
+        self.game_match.record(result);
+        if !self.game_match.is_over() {
+            return false;
+        }
+
+        self.instructions = match self.game_match.winner() {
+            Some(winner) => {
+                let winner_name = match (self.game_state.mode, winner) {
+                    (GameMode::VsComputer, Player::Cross) => String::from("You win"),
+                    _ => format!("{} wins", self.game_state.player_name(winner)),
+                };
+                format!(
+                    "{} the match {}-{}! Press {} to start a new match.",
+                    winner_name,
+                    self.game_match.wins(winner),
+                    self.game_match.wins(winner.opponent()),
+                    self.bindings.key_name(Command::NewGame)
+                )
+            }
+            None => format!(
+                "The match is tied {}-{}. Press {} to start a new match.",
+                self.game_match.wins(Player::Cross),
+                self.game_match.wins(Player::Nought),
+                self.bindings.key_name(Command::NewGame)
+            ),
         };
+        true
     }
-}
 
-#[derive(Clone, Copy, Debug)]
-pub struct BoardState {
-    pub cells: [[BoardCellState; BOARD_ROWS]; BOARD_COLUMNS],
-}
+    // Tells the computer how the game ended, or how the match ended if the game decided it.
+    fn send_game_over(
+        &mut self,
+        result: GameResult,
+        trigger: Trigger,
+        computer_sender: &Sender<Trigger>,
+    ) {
+        let trigger = if self.update_match(result) {
+            Trigger::MatchOver(
+                self.turn_id,
+                self.game_state.computer_character,
+                self.game_match.winner(),
+            )
+        } else {
+            trigger
+        };
+        computer_sender.send(trigger).unwrap();
+    }
 
-impl BoardState {
-    pub fn default() -> BoardState {
-        BoardState {
-            cells: [
-                [
-                    BoardCellState::NotSelected(BoardCell::Empty),
-                    BoardCellState::NotSelected(BoardCell::Empty),
-                    BoardCellState::NotSelected(BoardCell::Empty),
-                ],
-                [
-                    BoardCellState::NotSelected(BoardCell::Empty),
-                    BoardCellState::Selected(BoardCell::Empty),
-                    BoardCellState::NotSelected(BoardCell::Empty),
-                ],
-                [
-                    BoardCellState::NotSelected(BoardCell::Empty),
-                    BoardCellState::NotSelected(BoardCell::Empty),
-                    BoardCellState::NotSelected(BoardCell::Empty),
-                ],
-            ],
-        }
-    }
-
-    pub fn try_place_token(
+    pub fn computer_place_token(
         &mut self,
         (row, column): (usize, usize),
-        player: Player,
-    ) -> PlaceTokenResult {
-        match (self.cells[row][column], player) {
-            (BoardCellState::Selected(BoardCell::Empty), _) => {
-                self.cells[row][column] = BoardCellState::Selected(BoardCell::Occupied(player));
-                match self.check_for_winner() {
-                    Some(player) => PlaceTokenResult::SuccessWithWinner(player),
-                    None => PlaceTokenResult::Success,
+        computer_sender: &Sender<Trigger>,
+    ) {
+        let result = self.game_state.place_token((row, column), Player::Nought);
+        self.handle_computer_place_token_result(result, computer_sender);
+    }
+
+    pub fn is_current_turn(&self, turn_id: TurnId) -> bool {
+        self.turn_id == turn_id
+    }
+
+    fn next_turn_id(&mut self) -> TurnId {
+        self.turn_id.turn += 1;
+        self.turn_id
+    }
+
+    pub fn start_network_game(&mut self, role: Role, name: &str) {
+        self.game_state.mode = GameMode::Network(role);
+        self.game_state.players = [
+            PlayerDetails {
+                name: String::from(name),
+                token: role.token(),
+            },
+            PlayerDetails {
+                name: match role {
+                    Role::Host => String::from("Guest"),
+                    Role::Guest => String::from("Host"),
+                },
+                token: match role {
+                    Role::Host => Role::Guest.token(),
+                    Role::Guest => Role::Host.token(),
+                },
+            },
+        ];
+        self.instructions = match role {
+            Role::Host => String::from("Waiting for someone to join the game."),
+            Role::Guest => String::from("Connecting to the host."),
+        };
+    }
+
+    pub fn handle_network_event(&mut self, event: NetworkEvent) {
+        let peer_name = self.game_state.player_name(Player::Nought);
+
+        match event {
+            NetworkEvent::Connected(peer) => {
+                self.peer = Some(peer);
+                let name = self.game_state.player_details(Player::Cross).name.clone();
+                self.send_to_peer(Message::Hello(name));
+                self.instructions = match self.game_state.mode {
+                    GameMode::Network(Role::Host) => format!(
+                        "Someone has joined! Press {} to start the game.",
+                        self.bindings.key_name(Command::Start)
+                    ),
+                    _ => String::from("Connected! Waiting for the host to start the game."),
+                };
+            }
+            NetworkEvent::Received(Message::Hello(name)) => {
+                if !name.trim().is_empty() {
+                    self.game_state.players[Player::Nought.index()].name =
+                        String::from(name.trim());
+                }
+                if self.game_state.mode == GameMode::Network(Role::Host) {
+                    self.instructions = format!(
+                        "{} has joined! Press {} to start the game.",
+                        name,
+                        self.bindings.key_name(Command::Start)
+                    );
                 }
             }
-            (BoardCellState::Selected(BoardCell::Occupied(Player::User)), _) => {
-                PlaceTokenResult::OccupiedByUser
+            NetworkEvent::Received(Message::Start(rules, first)) => {
+                if self.game_state.mode != GameMode::Network(Role::Guest) {
+                    self.send_to_peer(Message::Error(String::from("only the host starts games")));
+                } else if !rules.is_valid() {
+                    self.send_to_peer(Message::Error(format!("{} isn't a valid board", rules)));
+                } else {
+                    self.game_state.board_state = BoardState::new(rules);
+                    self.restart_game();
+                    self.game_state.started = true;
+                    self.game_state.current_turn = match first {
+                        Role::Guest => Player::Cross,
+                        Role::Host => Player::Nought,
+                    };
+                    self.instructions = self.network_turn_instructions();
+                }
             }
-            (BoardCellState::Selected(BoardCell::Occupied(Player::Computer)), _) => {
-                PlaceTokenResult::OccupiedByComputer
+            NetworkEvent::Received(Message::Move(row, column)) => {
+                self.peer_place_token((row, column));
             }
-            (BoardCellState::NotSelected(_), Player::Computer) => {
-                self.cells[row][column] = BoardCellState::NotSelected(BoardCell::Occupied(player));
-
-                match self.check_for_winner() {
-                    Some(player) => PlaceTokenResult::SuccessWithWinner(player),
-                    None => PlaceTokenResult::Success,
+            NetworkEvent::Received(Message::Chat(text)) => {
+                self.show_chat(format!("{}: {}", peer_name, text));
+            }
+            NetworkEvent::Received(Message::Error(reason)) => {
+                // The other side only answers a move if it's illegal, so when our move is the
+                // last one it's the one that was rejected.
+                if self.game_state.started
+                    && matches!(
+                        self.game_state.moves.last(),
+                        Some(Move {
+                            player: Player::Cross,
+                            ..
+                        })
+                    )
+                {
+                    self.game_state.undo();
+                    self.game_state.winner = None;
+                    self.instructions = format!(
+                        "{} couldn't use your move: {}. {}",
+                        peer_name,
+                        reason,
+                        self.network_turn_instructions()
+                    );
+                } else {
+                    self.instructions = format!("{} couldn't use that: {}", peer_name, reason);
                 }
             }
-            (BoardCellState::NotSelected(_), Player::User) => PlaceTokenResult::Error,
+            NetworkEvent::Received(Message::Bye) | NetworkEvent::Disconnected => {
+                self.peer = None;
+                self.game_state.started = false;
+                self.instructions = match self.game_state.mode {
+                    // The host stops listening once someone has joined.
+                    GameMode::Network(Role::Host) => format!(
+                        "{} has left the game. Host it again to let someone else join.",
+                        peer_name
+                    ),
+                    _ => format!("{} has left the game.", peer_name),
+                };
+            }
+            NetworkEvent::Invalid(error) => {
+                self.send_to_peer(Message::Error(error));
+            }
         }
     }
 
-    // You know the code logic is awful when you have to add comments to explain what you're doing.
-    // This function will loop through the board cells and compare them to a list of winning combinations.
-    // It will return a winning player if all the cells in a winning combinations are occupied by the same player.
-    // The winning_combinations consists of three tuples containing a row & column index within an array nested inside
-    // another array. Simples
-    fn check_for_winner(self) -> Option<Player> {
-        let (_winner, player) = BoardState::winning_combinations().iter().fold(
-            (false, None),
-            |(winning_combination, player): (bool, Option<Player>), combination| {
-                // If there is a winning combination we no longer need to continue checking.
-                if winning_combination {
-                    return (true, player);
-                }
+    // Moves from the other player are checked before they're placed, since there's nothing
+    // stopping them from sending anything they like.
+    fn peer_place_token(&mut self, (row, column): (usize, usize)) {
+        let BoardRules { rows, columns, .. } = self.game_state.board_state.rules();
 
-                let (winner, player): (Option<bool>, Option<Player>) = combination.iter().fold(
-                    (None, None),
-                    |(valid_combination, player): (Option<bool>, Option<Player>), (row, column)| {
-                        if Some(false) == valid_combination {
-                            return (Some(false), None);
-                        }
-
-                        match self.cells[*row][*column] {
-                            BoardCellState::NotSelected(BoardCell::Occupied(Player::User))
-                            | BoardCellState::Selected(BoardCell::Occupied(Player::User)) => {
-                                // Checks to see if the player is None which will mean it's the first loop
-                                // for this combination. Or if the player is the User which means the previous
-                                // cell in the combination was occupied by the user.
-                                if player == None || player.unwrap() == Player::User {
-                                    return (Some(true), Some(Player::User));
-                                } else {
-                                    return (Some(false), None);
-                                }
-                            }
-                            BoardCellState::NotSelected(BoardCell::Occupied(Player::Computer))
-                            | BoardCellState::Selected(BoardCell::Occupied(Player::Computer)) => {
-                                if player == None || player.unwrap() == Player::Computer {
-                                    return (Some(true), Some(Player::Computer));
-                                } else {
-                                    return (Some(false), None);
-                                }
-                            }
-                            _ => return (Some(false), None),
-                        }
-                    },
-                );
+        let reason =
+            if !self.game_state.started || self.game_state.winner.is_some() || self.game_finished()
+            {
+                Some("the game isn't being played")
+            } else if self.game_state.current_turn != Player::Nought {
+                Some("it isn't your turn")
+            } else if row >= rows || column >= columns {
+                Some("that cell isn't on the board")
+            } else if let BoardCell::Occupied(_) = self.game_state.board_state.cell((row, column)) {
+                Some("that cell is already occupied")
+            } else {
+                None
+            };
 
-                (winner.unwrap(), player)
-            },
-        );
+        if let Some(reason) = reason {
+            self.send_to_peer(Message::Error(String::from(reason)));
+            self.instructions = format!(
+                "{} tried to make an illegal move: {}.",
+                self.game_state.player_name(Player::Nought),
+                reason
+            );
+            return;
+        }
 
-        player
+        let result = self.game_state.place_token((row, column), Player::Nought);
+        self.handle_network_place_token_result(result, Player::Nought);
     }
 
-    pub fn winning_combinations() -> &'static [[(usize, usize); 3]; 8] {
-        const WINNING_COMBINATIONS: [[(usize, usize); 3]; 8] = [
-            [(0, 0), (0, 1), (0, 2)],
-            [(1, 0), (1, 1), (1, 2)],
-            [(2, 0), (2, 1), (2, 2)],
-            [(0, 0), (1, 0), (2, 0)],
-            [(0, 1), (1, 1), (2, 1)],
-            [(0, 2), (1, 2), (2, 2)],
-            [(0, 0), (1, 1), (2, 2)],
-            [(0, 2), (1, 1), (2, 0)],
-        ];
-        &WINNING_COMBINATIONS
+    fn network_turn_instructions(&self) -> String {
+        match self.game_state.current_turn {
+            Player::Cross => format!(
+                "Your turn ({}). {}",
+                self.game_state.player_details(Player::Cross).token,
+                self.place_instructions()
+            ),
+            Player::Nought => format!(
+                "{}s turn. Please wait.",
+                self.game_state.player_name(Player::Nought)
+            ),
+        }
     }
-}
 
-pub enum PlaceTokenResult {
-    SuccessWithWinner(Player),
-    Success,
-    OccupiedByUser,
-    OccupiedByComputer,
-    Error,
-}
+    // Tells whoever is up in two player mode that it's their turn.
+    fn turn_instructions(&self) -> String {
+        format!(
+            "{}'s turn ({}). {}",
+            self.game_state.player_name(self.game_state.current_turn),
+            self.game_state
+                .player_details(self.game_state.current_turn)
+                .token,
+            self.place_instructions()
+        )
+    }
+
+    // The instructions name the keys in use, so they're still right with a bindings file.
+    fn place_instructions(&self) -> String {
+        format!(
+            "Press {} to place your token.",
+            self.bindings.key_name(Command::Place)
+        )
+    }
+
+    fn start_instructions(&self) -> String {
+        format!(
+            "Press {} to start game.",
+            self.bindings.key_name(Command::Start)
+        )
+    }
+
+    fn new_game_instructions(&self) -> String {
+        format!(
+            "Press {} to clear the board and {} to start a new game.",
+            self.bindings.key_name(Command::NewGame),
+            self.bindings.key_name(Command::Start)
+        )
+    }
+
+    // The host starts network games, so the guest only clears the board.
+    fn network_new_game_instructions(&self) -> String {
+        format!(
+            "Press {} to clear the board. The host can then start a new game.",
+            self.bindings.key_name(Command::NewGame)
+        )
+    }
+
+    fn send_to_peer(&mut self, message: Message) {
+        if let Some(peer) = &mut self.peer {
+            if peer.send(&message).is_err() {
+                self.peer = None;
+                self.game_state.started = false;
+                self.instructions = String::from("Lost the connection to the other player.");
+            }
+        }
+    }
+
+    pub fn leave_network_game(&mut self) {
+        self.send_to_peer(Message::Bye);
+        self.peer = None;
+    }
+
+    pub fn start_chat(&mut self) {
+        if self.peer.is_some() {
+            self.chat_draft = Some(String::new());
+        } else {
+            self.instructions = String::from("Chat is only available in a network game.");
+        }
+    }
+
+    pub fn send_chat(&mut self) {
+        if let Some(text) = self.chat_draft.take() {
+            if !text.is_empty() {
+                self.show_chat(format!("You: {}", text));
+                self.send_to_peer(Message::Chat(text));
+            }
+        }
+    }
+
+    pub fn save_game(&mut self) {
+        let record = GameRecord::from_game_state(&self.game_state);
+
+        let path = GameRecord::path();
+        self.instructions = match record.save(&path) {
+            Ok(()) => format!("Game saved to {}.", path.display()),
+            Err(error) => format!("Couldn't save the game: {}", error),
+        };
+    }
+
+    // Loads the saved game and carries on from where it was left. If it's the computer's turn it
+    // starts thinking straight away.
+    pub fn load_game(&mut self, computer_sender: &Sender<Trigger>) {
+        if let GameMode::Network(_) = self.game_state.mode {
+            self.instructions = String::from("Unable to load a game during a network game.");
+            return;
+        }
+
+        let game_state = match GameRecord::load(&GameRecord::path())
+            .map_err(|error| error.to_string())
+            .and_then(|record| self.game_state.replay_record(&record))
+        {
+            Ok(game_state) => game_state,
+            Err(error) => {
+                self.instructions = format!("Couldn't load the game: {}", error);
+                return;
+            }
+        };
+
+        // Anything the computer was doing belongs to the game being replaced.
+        self.restart_game();
+        computer_sender.send(Trigger::Cancel).unwrap();
+        self.game_state = game_state;
+        // The loaded board can be a different size, so the cursor has to be centred on it instead.
+        self.cursor_location = CursorLocation::centre_of(&self.game_state.board_state);
+
+        self.instructions = match (self.game_state.mode, self.game_state.current_turn) {
+            _ if self.game_state.winner.is_some() || self.game_finished() => {
+                format!("Loaded a finished game. {}", self.new_game_instructions())
+            }
+            (GameMode::VsComputer, Player::Nought) => {
+                let turn_id = self.next_turn_id();
+                let trigger = if self.game_state.moves.is_empty() {
+                    Trigger::ComputersTurnFirst(turn_id, self.game_state.clone())
+                } else {
+                    Trigger::ComputersTurn(turn_id, self.game_state.clone())
+                };
+                computer_sender.send(trigger).unwrap();
+                format!("Game loaded. {}s turn.", self.game_state.computer_character)
+            }
+            (GameMode::VsComputer, Player::Cross) => {
+                format!("Game loaded. {}", self.place_instructions())
+            }
+            _ => format!("Game loaded. {}", self.turn_instructions()),
+        };
+    }
+
+    pub fn start_replay(&mut self) {
+        if self.game_state.started && !self.game_finished() && self.game_state.winner.is_none() {
+            self.instructions =
+                String::from("Unable to watch a replay while the game is being played.");
+            return;
+        }
+
+        // Loading it through a game state checks the moves before anything is shown.
+        match GameRecord::load(&GameRecord::path())
+            .map_err(|error| error.to_string())
+            .and_then(|record| {
+                self.game_state.replay_record(&record)?;
+                Ok(record)
+            }) {
+            Ok(record) => {
+                let mut replay = Replay::new(record);
+                replay.annotate();
+                self.replay = Some(replay);
+            }
+            Err(error) => self.instructions = format!("Couldn't load the game: {}", error),
+        }
+    }
+
+    // Shows the line in the chat box and keeps it with the game so it can be seen again in a replay.
+    pub fn show_chat(&mut self, text: String) {
+        self.game_state
+            .chat
+            .push((self.game_state.moves.len(), text.clone()));
+        self.chat = text;
+    }
+
+    pub fn move_cursor_location(&mut self, row: usize, column: usize) {
+        self.cursor_location = CursorLocation { row, column };
+    }
+
+    pub fn restart_game(&mut self) {
+        self.instructions = self.start_instructions();
+        self.game_state.board_state = BoardState::new(self.game_state.board_state.rules());
+        self.cursor_location = CursorLocation::centre_of(&self.game_state.board_state);
+        self.game_state.winner = None;
+        self.game_state.started = false;
+        self.game_state.moves.clear();
+        self.game_state.undone_moves.clear();
+        self.game_state.chat.clear();
+        self.turn_id = TurnId {
+            game: self.turn_id.game + 1,
+            turn: 0,
+        };
+    }
 
-impl PlaceTokenResult {
-    pub fn handle_computer_place_token_result(
-        self,
-        app: &mut App,
+    fn handle_computer_place_token_result(
+        &mut self,
+        result: PlaceTokenResult,
         computer_sender: &Sender<Trigger>,
     ) {
-        match self {
+        match result {
             PlaceTokenResult::Success => {
-                if app.game_finished() && app.game_state.winner.is_none() {
-                    computer_sender.send(Trigger::Draw).unwrap();
+                if self.game_finished() && self.game_state.winner.is_none() {
+                    self.record_result(GameResult::Draw);
 
-                    app.instructions = String::from(
-                        "It's a tie. Press N to clear the board and S to start a new game.",
+                    self.instructions = format!("It's a tie. {}", self.new_game_instructions());
+                    self.send_game_over(
+                        GameResult::Draw,
+                        Trigger::Draw(self.turn_id),
+                        computer_sender,
                     );
                 } else {
-                    app.chat = format!("{}: Ok, your turn!", app.game_state.computer_character);
-                    app.instructions = String::from("Press enter to your place token.");
-                    app.game_state.swap_current_turn()
+                    self.show_chat(format!(
+                        "{}: Ok, your turn!",
+                        self.game_state.computer_character
+                    ));
+                    self.instructions = self.place_instructions();
+                    self.game_state.swap_current_turn()
                 }
             }
             PlaceTokenResult::SuccessWithWinner(_player) => {
-                app.game_state.winner = Some(Player::Computer);
-                app.instructions = format!(
-                    "{} wins! Press N to clear the board and S to start a new game.",
-                    app.game_state.computer_character
+                self.game_state.winner = Some(Player::Nought);
+                self.instructions = format!(
+                    "{} wins! {}",
+                    self.game_state.computer_character,
+                    self.new_game_instructions()
+                );
+                self.record_result(GameResult::ComputerWon);
+
+                self.send_game_over(
+                    GameResult::ComputerWon,
+                    Trigger::Winner(self.turn_id, self.bindings.key_name(Command::NewGame)),
+                    computer_sender,
                 );
-                computer_sender.send(Trigger::Winner).unwrap();
             }
             _ => {
-                app.instructions = String::from("Oops, something went wrong.");
+                self.instructions = String::from("Oops, something went wrong.");
+            }
+        }
+    }
+
+    fn handle_network_place_token_result(&mut self, result: PlaceTokenResult, placed_by: Player) {
+        match result {
+            PlaceTokenResult::Success => {
+                if self.game_finished() {
+                    self.instructions =
+                        format!("It's a tie. {}", self.network_new_game_instructions());
+                } else {
+                    self.game_state.swap_current_turn();
+                    self.instructions = self.network_turn_instructions();
+                }
+            }
+            PlaceTokenResult::SuccessWithWinner(player) => {
+                self.game_state.winner = Some(player);
+                self.instructions = match placed_by {
+                    Player::Cross => format!("You win! {}", self.network_new_game_instructions()),
+                    Player::Nought => format!(
+                        "{} wins! {}",
+                        self.game_state.player_name(Player::Nought),
+                        self.network_new_game_instructions()
+                    ),
+                };
+            }
+            PlaceTokenResult::Occupied(Player::Cross) => {
+                self.instructions = String::from("This cell is already occupied by you.");
+            }
+            PlaceTokenResult::Occupied(Player::Nought) => {
+                self.instructions = format!(
+                    "This cell is already occupied by {}.",
+                    self.game_state.player_name(Player::Nought)
+                );
+            }
+            PlaceTokenResult::Error => {
+                self.instructions = String::from("Oops, something went wrong.");
+            }
+        }
+    }
+
+    fn handle_hot_seat_place_token_result(&mut self, result: PlaceTokenResult) {
+        match result {
+            PlaceTokenResult::Success => {
+                if self.game_finished() {
+                    self.instructions = format!("It's a tie. {}", self.new_game_instructions());
+                    self.update_match(GameResult::Draw);
+                } else {
+                    self.game_state.swap_current_turn();
+                    self.instructions = self.turn_instructions();
+                }
+            }
+            PlaceTokenResult::SuccessWithWinner(player) => {
+                self.game_state.winner = Some(player);
+                self.instructions = format!(
+                    "{} wins! {}",
+                    self.game_state.player_name(player),
+                    self.new_game_instructions()
+                );
+                self.update_match(match player {
+                    Player::Cross => GameResult::UserWon,
+                    Player::Nought => GameResult::ComputerWon,
+                });
+            }
+            PlaceTokenResult::Occupied(Player::Cross) => {
+                self.instructions = format!(
+                    "This cell is already occupied by {}.",
+                    self.game_state.player_name(Player::Cross)
+                );
+            }
+            PlaceTokenResult::Occupied(Player::Nought) => {
+                self.instructions = format!(
+                    "This cell is already occupied by {}.",
+                    self.game_state.player_name(Player::Nought)
+                );
+            }
+            PlaceTokenResult::Error => {
+                self.instructions = String::from("Oops, something went wrong.");
             }
         }
     }
 
-    pub fn handle_user_place_token_result(self, app: &mut App, computer_sender: &Sender<Trigger>) {
-        match self {
+    fn handle_user_place_token_result(
+        &mut self,
+        result: PlaceTokenResult,
+        computer_sender: &Sender<Trigger>,
+    ) {
+        match result {
             PlaceTokenResult::Success => {
-                app.game_state.swap_current_turn();
+                self.game_state.swap_current_turn();
 
-                if app.game_finished() && app.game_state.winner.is_none() {
-                    computer_sender.send(Trigger::Draw).unwrap();
+                if self.game_finished() && self.game_state.winner.is_none() {
+                    self.record_result(GameResult::Draw);
 
-                    app.instructions = String::from(
-                        "It's a tie. Press N to clear the board and S to start a new game.",
+                    self.instructions = format!("It's a tie. {}", self.new_game_instructions());
+                    self.send_game_over(
+                        GameResult::Draw,
+                        Trigger::Draw(self.turn_id),
+                        computer_sender,
                     );
                 } else {
+                    let turn_id = self.next_turn_id();
                     computer_sender
-                        .send(Trigger::ComputersTurn(app.game_state))
+                        .send(Trigger::ComputersTurn(turn_id, self.game_state.clone()))
                         .unwrap();
 
-                    app.instructions = format!("{}s turn.", app.game_state.computer_character);
+                    self.instructions = format!("{}s turn.", self.game_state.computer_character);
                 }
             }
             PlaceTokenResult::SuccessWithWinner(_player) => {
-                app.game_state.winner = Some(Player::User);
-                app.instructions = String::from(
-                    "You win! Press N to clear the game board and S to start a new game.",
+                self.game_state.winner = Some(Player::Cross);
+                self.instructions = format!("You win! {}", self.new_game_instructions());
+                self.record_result(GameResult::UserWon);
+
+                self.send_game_over(
+                    GameResult::UserWon,
+                    Trigger::Loser(self.turn_id),
+                    computer_sender,
                 );
-                computer_sender.send(Trigger::Loser).unwrap();
             }
-            PlaceTokenResult::OccupiedByComputer => {
-                app.instructions = format!(
+            PlaceTokenResult::Occupied(Player::Nought) => {
+                self.instructions = format!(
                     "This cell is already occupied by {}.",
-                    app.game_state.computer_character
+                    self.game_state.computer_character
                 );
             }
-            PlaceTokenResult::OccupiedByUser => {
-                app.instructions = String::from("This cell is already occupied by you.");
+            PlaceTokenResult::Occupied(Player::Cross) => {
+                self.instructions = String::from("This cell is already occupied by you.");
             }
             PlaceTokenResult::Error => {
-                app.instructions = String::from("Oops, something went wrong.");
+                self.instructions = String::from("Oops, something went wrong.");
             }
         }
     }
 }
 
 #[derive(Clone, Copy, Debug)]
-pub enum BoardCellState {
-    Selected(BoardCell),
-    NotSelected(BoardCell),
+pub struct CursorLocation {
+    pub row: usize,
+    pub column: usize,
 }
-#[derive(Clone, Copy, Debug)]
-pub enum BoardCell {
-    Empty,
-    Occupied(Player),
+
+impl CursorLocation {
+    pub fn centre_of(board_state: &BoardState) -> CursorLocation {
+        let (row, column) = board_state.centre();
+        CursorLocation { row, column }
+    }
 }
 
-impl Display for BoardCell {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let board_cell = match &self {
-            BoardCell::Empty => "  *  ",
-            BoardCell::Occupied(Player::Computer) => "  o  ",
-            BoardCell::Occupied(Player::User) => "  x  ",
+#[derive(Clone, Debug)]
+pub struct GameState {
+    pub board_state: BoardState,
+    pub current_turn: Player,
+    pub difficulty_level: Level,
+    pub started: bool,
+    pub computer_character: Character,
+    // Every token placed so far this game, in the order they were placed.
+    pub moves: Vec<Move>,
+    // Moves taken back with undo. The next move to redo is at the end.
+    pub undone_moves: Vec<Move>,
+    // Everything said in the chat box this game, along with how many moves had been played when it
+    // was said.
+    pub chat: Vec<(usize, String)>,
+    pub mode: GameMode,
+    // Indexed by Player. The user always plays Cross, and in two player mode the second person plays
+    // Nought.
+    pub players: [PlayerDetails; 2],
+    winner: Option<Player>,
+}
+
+impl GameState {
+    pub fn swap_current_turn(&mut self) {
+        self.current_turn = self.current_turn.opponent();
+    }
+
+    pub fn winner(&self) -> Option<Player> {
+        self.winner
+    }
+
+    // Plays the record's moves out on a fresh board with its settings. Records can be edited by
+    // hand, so every move is checked and the result has to match what actually happened.
+    pub fn replay_record(&self, record: &GameRecord) -> Result<GameState, String> {
+        let mut game_state = self.clone();
+        game_state.board_state = BoardState::new(record.rules);
+        game_state.mode = record.mode;
+        game_state.difficulty_level = record.level;
+        game_state.computer_character = record.character;
+        game_state.current_turn = record.first;
+        game_state.started = true;
+        game_state.winner = None;
+        game_state.moves.clear();
+        game_state.undone_moves.clear();
+        game_state.chat = record.chat.clone();
+
+        for (row, column) in record.moves.iter().copied() {
+            if game_state.winner.is_some() {
+                return Err(String::from("there are moves after the game was won"));
+            }
+
+            match game_state.place_token((row, column), game_state.current_turn) {
+                PlaceTokenResult::Success => game_state.swap_current_turn(),
+                PlaceTokenResult::SuccessWithWinner(player) => game_state.winner = Some(player),
+                _ => return Err(format!("{},{} can't be played", row, column)),
+            }
+        }
+
+        if RecordResult::of(&game_state) != record.result {
+            return Err(String::from("the result doesn't match the moves"));
+        }
+
+        Ok(game_state)
+    }
+
+    pub fn player_details(&self, player: Player) -> &PlayerDetails {
+        &self.players[player.index()]
+    }
+
+    // Against the computer it's always you versus the selected character.
+    pub fn player_name(&self, player: Player) -> String {
+        match (self.mode, player) {
+            (GameMode::VsComputer, Player::Cross) => String::from("You"),
+            (GameMode::VsComputer, Player::Nought) => self.computer_character.to_string(),
+            (GameMode::HotSeat, player) => self.player_details(player).name.clone(),
+            (GameMode::Network(_), Player::Cross) => String::from("You"),
+            (GameMode::Network(_), Player::Nought) => self.player_details(player).name.clone(),
+        }
+    }
+
+    pub fn cell_symbol(&self, cell: BoardCell) -> String {
+        match cell {
+            BoardCell::Empty => cell.to_string(),
+            BoardCell::Occupied(player) => self.player_details(player).token.to_string(),
+        }
+    }
+
+    pub fn place_token(
+        &mut self,
+        (row, column): (usize, usize),
+        player: Player,
+    ) -> PlaceTokenResult {
+        let result = self.board_state.try_place_token((row, column), player);
+
+        if let PlaceTokenResult::Success | PlaceTokenResult::SuccessWithWinner(_) = result {
+            self.moves.push(Move {
+                player,
+                row,
+                column,
+                turn: self.moves.len() + 1,
+            });
+            // Playing a new move replaces whatever was undone.
+            self.undone_moves.clear();
+        }
+
+        result
+    }
+
+    // Takes back moves until the user's last move has been removed, so that it's the user's turn again.
+    // That will usually be the computer's reply followed by the user's move. In two player mode only
+    // the last move is taken back.
+    pub fn undo(&mut self) {
+        if self.mode == GameMode::HotSeat {
+            if let Some(last_move) = self.moves.pop() {
+                self.board_state
+                    .set_cell((last_move.row, last_move.column), BoardCell::Empty);
+                self.undone_moves.push(last_move);
+                self.current_turn = last_move.player;
+            }
+            self.forget_undone_chat();
+            return;
+        }
+
+        while let Some(last_move) = self.moves.pop() {
+            self.board_state
+                .set_cell((last_move.row, last_move.column), BoardCell::Empty);
+            self.undone_moves.push(last_move);
+
+            if last_move.player == Player::Cross {
+                break;
+            }
+        }
+
+        self.current_turn = Player::Cross;
+        self.forget_undone_chat();
+    }
+
+    // Whatever was said about moves that have been taken back no longer fits the game.
+    fn forget_undone_chat(&mut self) {
+        let moves = self.moves.len();
+        self.chat.retain(|(turn, _)| *turn <= moves);
+    }
+
+    // Puts back the user's next undone move and the computer's reply to it, if it had one.
+    // Returns false when there is nothing to redo.
+    pub fn redo(&mut self) -> bool {
+        if self.mode == GameMode::HotSeat {
+            return match self.undone_moves.pop() {
+                Some(next_move) => {
+                    self.replay_move(next_move);
+                    self.current_turn = next_move.player.opponent();
+                    true
+                }
+                None => false,
+            };
+        }
+
+        let user_move = match self.undone_moves.pop() {
+            Some(user_move) => user_move,
+            None => return false,
         };
+        self.replay_move(user_move);
+        self.current_turn = Player::Nought;
+
+        if let Some(Move {
+            player: Player::Nought,
+            ..
+        }) = self.undone_moves.last()
+        {
+            let computer_move = self.undone_moves.pop().unwrap();
+            self.replay_move(computer_move);
+            self.current_turn = Player::Cross;
+        }
 
-        f.write_str(board_cell)
+        true
     }
-}
 
-impl Default for BoardCellState {
-    fn default() -> Self {
-        BoardCellState::NotSelected(BoardCell::Empty)
+    fn replay_move(&mut self, played_move: Move) {
+        self.board_state.set_cell(
+            (played_move.row, played_move.column),
+            BoardCell::Occupied(played_move.player),
+        );
+        self.moves.push(played_move);
     }
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
-pub enum Player {
-    User,
-    Computer,
+pub enum GameMode {
+    VsComputer,
+    // Two people taking turns on the same keyboard.
+    HotSeat,
+    // Playing someone else over TCP. This side plays Cross and the other side plays Nought.
+    Network(Role),
 }
 
-#[derive(Clone, Copy, Debug)]
-pub enum Level {
-    Easy,
-    Hard,
-}
-
-impl Display for Level {
+impl Display for GameMode {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let level = match &self {
-            Level::Easy => "Easy",
-            Level::Hard => "Hard",
+        let mode = match &self {
+            GameMode::VsComputer => "Against the computer",
+            GameMode::HotSeat => "Two players",
+            GameMode::Network(Role::Host) => "Network game (hosting)",
+            GameMode::Network(Role::Guest) => "Network game (joined)",
         };
 
-        f.write_str(level)
+        f.write_str(mode)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct PlayerDetails {
+    pub name: String,
+    pub token: char,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GameResult {
+    UserWon,
+    ComputerWon,
+    Draw,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    // An app with a game in play and the given moves on the board, taking turns from the first one.
+    fn game_in_play(mode: GameMode, first: Player, cells: &[(usize, usize)]) -> App {
+        let mut app = App::default();
+        app.game_state.mode = mode;
+        app.game_state.started = true;
+        app.game_state.current_turn = first;
+        for cell in cells {
+            let player = app.game_state.current_turn;
+            app.game_state.place_token(*cell, player);
+            app.game_state.swap_current_turn();
+        }
+        app
+    }
+
+    fn cells(app: &App) -> Vec<(usize, usize)> {
+        app.game_state
+            .moves
+            .iter()
+            .map(|played_move| (played_move.row, played_move.column))
+            .collect()
+    }
+
+    #[test]
+    fn several_moves_can_be_undone_and_redone() {
+        let played = [(1, 1), (0, 0), (2, 2), (0, 2)];
+        let mut app = game_in_play(GameMode::VsComputer, Player::Cross, &played);
+        let (sender, _receiver) = mpsc::channel();
+
+        app.undo(&sender);
+        assert_eq!(cells(&app), played[..2]);
+        app.undo(&sender);
+        assert!(cells(&app).is_empty());
+        assert_eq!(app.game_state.board_state.empty_cells().len(), 9);
+        assert_eq!(app.game_state.current_turn, Player::Cross);
+
+        app.redo(&sender);
+        assert_eq!(cells(&app), played[..2]);
+        app.redo(&sender);
+        assert_eq!(cells(&app), played);
+        assert_eq!(app.game_state.current_turn, Player::Cross);
+        assert!(!app.game_state.redo());
+    }
+
+    #[test]
+    fn undo_stops_at_the_users_turn_when_the_computer_went_first() {
+        let mut app = game_in_play(
+            GameMode::VsComputer,
+            Player::Nought,
+            &[(1, 1), (0, 0), (2, 2)],
+        );
+        let (sender, _receiver) = mpsc::channel();
+
+        app.undo(&sender);
+        assert_eq!(cells(&app), [(1, 1)]);
+        assert_eq!(app.game_state.current_turn, Player::Cross);
+
+        // The computer's first move stays, since the user had no move before it.
+        app.undo(&sender);
+        assert_eq!(cells(&app), [(1, 1)]);
+        assert_eq!(app.game_state.current_turn, Player::Cross);
+    }
+
+    #[test]
+    fn two_players_undo_one_move_at_a_time() {
+        let played = [(1, 1), (0, 0), (2, 2)];
+        let mut app = game_in_play(GameMode::HotSeat, Player::Cross, &played);
+        let (sender, _receiver) = mpsc::channel();
+
+        app.undo(&sender);
+        assert_eq!(cells(&app), played[..2]);
+        assert_eq!(app.game_state.current_turn, Player::Cross);
+        app.undo(&sender);
+        assert_eq!(cells(&app), played[..1]);
+        assert_eq!(app.game_state.current_turn, Player::Nought);
+
+        app.redo(&sender);
+        assert_eq!(cells(&app), played[..2]);
+        assert_eq!(app.game_state.current_turn, Player::Cross);
+    }
+
+    #[test]
+    fn a_new_move_clears_the_moves_to_redo() {
+        let mut app = game_in_play(GameMode::HotSeat, Player::Cross, &[(1, 1), (0, 0)]);
+        let (sender, _receiver) = mpsc::channel();
+
+        app.undo(&sender);
+        assert_eq!(app.game_state.undone_moves.len(), 1);
+
+        app.game_state.place_token((2, 2), Player::Nought);
+        assert!(app.game_state.undone_moves.is_empty());
+        assert!(!app.game_state.redo());
+    }
+
+    #[test]
+    fn new_games_only_cancel_the_computer_when_it_is_playing() {
+        let finished = [(0, 0), (1, 0), (0, 1), (2, 0), (0, 2)];
+        for (mode, cancels) in [(GameMode::VsComputer, true), (GameMode::HotSeat, false)] {
+            let mut app = game_in_play(mode, Player::Cross, &finished);
+            app.game_state.winner = Some(Player::Cross);
+            let (sender, receiver) = mpsc::channel();
+
+            app.new_game(&sender);
+            assert!(app.game_state.moves.is_empty());
+            assert_eq!(
+                matches!(receiver.try_recv(), Ok(Trigger::Cancel)),
+                cancels,
+                "{:?}",
+                mode
+            );
+        }
+    }
+
+    fn play_network_move(app: &mut App, (row, column): (usize, usize)) {
+        let (sender, _receiver) = mpsc::channel();
+        app.cursor_location = CursorLocation { row, column };
+        app.enter(&sender);
+    }
+
+    fn reject_move(app: &mut App) {
+        app.handle_network_event(NetworkEvent::Received(Message::Error(String::from(
+            "that cell is already occupied",
+        ))));
+    }
+
+    #[test]
+    fn a_move_the_other_side_rejects_is_taken_back() {
+        let mut app = game_in_play(GameMode::Network(Role::Host), Player::Cross, &[(0, 0)]);
+        app.game_state.current_turn = Player::Nought;
+        app.peer_place_token((1, 1));
+        play_network_move(&mut app, (2, 2));
+        assert_eq!(app.game_state.current_turn, Player::Nought);
+
+        reject_move(&mut app);
+        assert_eq!(cells(&app), [(0, 0), (1, 1)]);
+        assert_eq!(app.game_state.board_state.cell((2, 2)), BoardCell::Empty);
+        assert_eq!(app.game_state.current_turn, Player::Cross);
+    }
+
+    #[test]
+    fn a_rejected_winning_move_no_longer_wins() {
+        let played = [(0, 0), (1, 0), (0, 1), (2, 0)];
+        let mut app = game_in_play(GameMode::Network(Role::Host), Player::Cross, &played);
+        play_network_move(&mut app, (0, 2));
+        assert_eq!(app.game_state.winner(), Some(Player::Cross));
+
+        reject_move(&mut app);
+        assert_eq!(cells(&app), played);
+        assert_eq!(app.game_state.winner(), None);
+        assert_eq!(app.game_state.current_turn, Player::Cross);
+    }
+
+    // Their own moves stay put when they send an error about something else.
+    #[test]
+    fn errors_after_the_other_sides_move_leave_the_board_alone() {
+        let mut app = game_in_play(GameMode::Network(Role::Host), Player::Cross, &[(0, 0)]);
+        app.peer_place_token((1, 1));
+
+        reject_move(&mut app);
+        assert_eq!(cells(&app), [(0, 0), (1, 1)]);
+        assert_eq!(app.game_state.current_turn, Player::Cross);
     }
 }
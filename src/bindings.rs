@@ -0,0 +1,449 @@
+// Which keys do what. The defaults can be changed with a file called `bindings` in a
+// `noughts_and_crosses` folder in the config directory, usually ~/.config. One command per line,
+// followed by the keys for it:
+//
+//   preset vim
+//   hint ?
+//   start s space
+//
+// `preset` picks the keys to start from: default, vim (hjkl to move) or wasd. Every other line
+// replaces the keys for a command, and takes those keys away from whatever they did before. Keys are
+// a single character or one of up, down, left, right, enter, esc, backspace and space. Place and
+// quit always need a key left, so a file that takes them all away is rejected. Letters work
+// with or without shift, unless the shifted letter is bound to something else. That's how the vim
+// preset has h to move left and H for Hard.
+//
+// `digits numpad` or `digits phone` picks how 1 to 9 are laid out on the 3x3 board, see
+// cell_entry.rs. Digits only pick cells if they aren't bound to a command.
+use std::{
+    fs,
+    io::{self, ErrorKind},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use crate::{cell_entry::DigitLayout, config, input::InputKey};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Command {
+    Up,
+    Down,
+    Left,
+    Right,
+    Place,
+    TypeCell,
+    Start,
+    NewGame,
+    Undo,
+    Redo,
+    ChangeOpponent,
+    ChangeBoard,
+    TwoPlayers,
+    Save,
+    Load,
+    Watch,
+    MatchFormat,
+    Hint,
+    Analysis,
+    JumpToMistake,
+    Chat,
+    Easy,
+    Medium,
+    Hard,
+    Impossible,
+    Adaptive,
+    MoreMistakes,
+    FewerMistakes,
+    Quit,
+}
+
+impl Command {
+    const ALL: [Command; 29] = [
+        Command::Up,
+        Command::Down,
+        Command::Left,
+        Command::Right,
+        Command::Place,
+        Command::TypeCell,
+        Command::Start,
+        Command::NewGame,
+        Command::Undo,
+        Command::Redo,
+        Command::ChangeOpponent,
+        Command::ChangeBoard,
+        Command::TwoPlayers,
+        Command::Save,
+        Command::Load,
+        Command::Watch,
+        Command::MatchFormat,
+        Command::Hint,
+        Command::Analysis,
+        Command::JumpToMistake,
+        Command::Chat,
+        Command::Easy,
+        Command::Medium,
+        Command::Hard,
+        Command::Impossible,
+        Command::Adaptive,
+        Command::MoreMistakes,
+        Command::FewerMistakes,
+        Command::Quit,
+    ];
+
+    // The name used for the command in the bindings file.
+    fn name(&self) -> &'static str {
+        match self {
+            Command::Up => "up",
+            Command::Down => "down",
+            Command::Left => "left",
+            Command::Right => "right",
+            Command::Place => "place",
+            Command::TypeCell => "type-cell",
+            Command::Start => "start",
+            Command::NewGame => "new-game",
+            Command::Undo => "undo",
+            Command::Redo => "redo",
+            Command::ChangeOpponent => "change-opponent",
+            Command::ChangeBoard => "change-board",
+            Command::TwoPlayers => "two-players",
+            Command::Save => "save",
+            Command::Load => "load",
+            Command::Watch => "watch",
+            Command::MatchFormat => "match-format",
+            Command::Hint => "hint",
+            Command::Analysis => "analysis",
+            Command::JumpToMistake => "jump-to-mistake",
+            Command::Chat => "chat",
+            Command::Easy => "easy",
+            Command::Medium => "medium",
+            Command::Hard => "hard",
+            Command::Impossible => "impossible",
+            Command::Adaptive => "adaptive",
+            Command::MoreMistakes => "more-mistakes",
+            Command::FewerMistakes => "fewer-mistakes",
+            Command::Quit => "quit",
+        }
+    }
+}
+
+impl FromStr for Command {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Command, String> {
+        Command::ALL
+            .into_iter()
+            .find(|command| command.name() == name)
+            .ok_or_else(|| format!("{} is not a command", name))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Preset {
+    Default,
+    // hjkl to move, with Hard, Jump and Load moved to H, J and L.
+    Vim,
+    // wasd to move, with Save, Adaptive and Start moved to W, A and S.
+    Wasd,
+}
+
+impl FromStr for Preset {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Preset, String> {
+        match name {
+            "default" => Ok(Preset::Default),
+            "vim" => Ok(Preset::Vim),
+            "wasd" => Ok(Preset::Wasd),
+            _ => Err(format!("{} is not default, vim or wasd", name)),
+        }
+    }
+}
+
+pub struct Bindings {
+    // Every key that does something, in the order they were bound.
+    keys: Vec<(InputKey, Command)>,
+    pub digit_layout: DigitLayout,
+}
+
+impl Bindings {
+    pub fn default() -> Bindings {
+        Bindings::preset(Preset::Default)
+    }
+
+    pub fn preset(preset: Preset) -> Bindings {
+        let mut bindings = Bindings {
+            keys: vec![
+                (InputKey::Up, Command::Up),
+                (InputKey::Down, Command::Down),
+                (InputKey::Left, Command::Left),
+                (InputKey::Right, Command::Right),
+                (InputKey::Enter, Command::Place),
+                (InputKey::Char('s'), Command::Start),
+                (InputKey::Char('n'), Command::NewGame),
+                (InputKey::Char('u'), Command::Undo),
+                (InputKey::Char('r'), Command::Redo),
+                (InputKey::Char('c'), Command::ChangeOpponent),
+                (InputKey::Char('b'), Command::ChangeBoard),
+                (InputKey::Char('p'), Command::TwoPlayers),
+                (InputKey::Char('w'), Command::Save),
+                (InputKey::Char('l'), Command::Load),
+                (InputKey::Char('v'), Command::Watch),
+                (InputKey::Char('g'), Command::MatchFormat),
+                (InputKey::Char('?'), Command::Hint),
+                (InputKey::Char('z'), Command::Analysis),
+                (InputKey::Char('j'), Command::JumpToMistake),
+                (InputKey::Char('t'), Command::Chat),
+                (InputKey::Char('e'), Command::Easy),
+                (InputKey::Char('m'), Command::Medium),
+                (InputKey::Char('h'), Command::Hard),
+                (InputKey::Char('i'), Command::Impossible),
+                (InputKey::Char('a'), Command::Adaptive),
+                (InputKey::Char('+'), Command::MoreMistakes),
+                (InputKey::Char('-'), Command::FewerMistakes),
+                (InputKey::Char(':'), Command::TypeCell),
+                (InputKey::Esc, Command::Quit),
+            ],
+            digit_layout: DigitLayout::Numpad,
+        };
+
+        // The arrow keys still move the cursor with either preset.
+        let changes: &[(Command, &[char])] = match preset {
+            Preset::Default => &[],
+            Preset::Vim => &[
+                (Command::Hard, &['H']),
+                (Command::JumpToMistake, &['J']),
+                (Command::Load, &['L']),
+                (Command::Left, &['h']),
+                (Command::Down, &['j']),
+                (Command::Up, &['k']),
+                (Command::Right, &['l']),
+            ],
+            Preset::Wasd => &[
+                (Command::Save, &['W']),
+                (Command::Adaptive, &['A']),
+                (Command::Start, &['S']),
+                (Command::Up, &['w']),
+                (Command::Left, &['a']),
+                (Command::Down, &['s']),
+                (Command::Right, &['d']),
+            ],
+        };
+        for (command, characters) in changes {
+            let mut keys: Vec<InputKey> = characters
+                .iter()
+                .map(|character| InputKey::Char(*character))
+                .collect();
+            if let Some(arrow) = arrow_for(*command) {
+                keys.push(arrow);
+            }
+            bindings.bind(*command, &keys);
+        }
+
+        bindings
+    }
+
+    // A missing file just means the default keys are used.
+    pub fn load(path: &Path) -> Result<Bindings, io::Error> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(error) if error.kind() == ErrorKind::NotFound => return Ok(Bindings::default()),
+            Err(error) => return Err(error),
+        };
+
+        text.parse()
+            .map_err(|error| io::Error::new(ErrorKind::InvalidData, error))
+    }
+
+    // Where the bindings file goes. None if there's no home directory to put it in.
+    pub fn path() -> Option<PathBuf> {
+        Some(config::directory()?.join("bindings"))
+    }
+
+    // Replaces the keys for the command. Any other command using one of the keys loses it.
+    fn bind(&mut self, command: Command, keys: &[InputKey]) {
+        self.keys
+            .retain(|(key, bound_command)| *bound_command != command && !keys.contains(key));
+        self.keys.extend(keys.iter().map(|key| (*key, command)));
+    }
+
+    pub fn command(&self, key: InputKey) -> Option<Command> {
+        let find = |key| {
+            self.keys
+                .iter()
+                .find(|(bound_key, _)| *bound_key == key)
+                .map(|(_, command)| *command)
+        };
+
+        match key {
+            InputKey::Char(character) if character.is_uppercase() => {
+                find(key).or_else(|| find(InputKey::Char(character.to_ascii_lowercase())))
+            }
+            _ => find(key),
+        }
+    }
+
+    // The first key bound to the command, for showing in the menu.
+    pub fn key_name(&self, command: Command) -> String {
+        self.keys
+            .iter()
+            .find(|(_, bound_command)| *bound_command == command)
+            .map_or(String::from("-"), |(key, _)| key.to_string())
+    }
+}
+
+impl FromStr for Bindings {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Bindings, String> {
+        let lines: Vec<Vec<&str>> = text
+            .lines()
+            .map(|line| line.split_whitespace().collect::<Vec<&str>>())
+            .filter(|words| !words.is_empty())
+            .collect();
+
+        // The preset has to be applied first so that the other lines can change it.
+        let mut bindings = match lines.iter().find(|words| words[0] == "preset") {
+            Some(words) if words.len() == 2 => Bindings::preset(words[1].parse()?),
+            Some(words) => return Err(format!("expected preset <name>: {}", words.join(" "))),
+            None => Bindings::default(),
+        };
+
+        for words in lines.iter().filter(|words| words[0] != "preset") {
+            if words[0] == "digits" {
+                bindings.digit_layout = match words[1..] {
+                    [layout] => layout.parse()?,
+                    _ => return Err(format!("expected digits <layout>: {}", words.join(" "))),
+                };
+                continue;
+            }
+
+            let command: Command = words[0].parse()?;
+            let keys = words[1..]
+                .iter()
+                .map(|word| parse_key(word))
+                .collect::<Result<Vec<InputKey>, String>>()?;
+
+            if keys.is_empty() {
+                return Err(format!("no keys given for {}", command.name()));
+            }
+            bindings.bind(command, &keys);
+        }
+
+        // Without these there'd be no way to play or to get out of the game.
+        for command in [Command::Place, Command::Quit] {
+            if !bindings.keys.iter().any(|(_, bound)| *bound == command) {
+                return Err(format!("{} has no keys left", command.name()));
+            }
+        }
+
+        Ok(bindings)
+    }
+}
+
+fn parse_key(word: &str) -> Result<InputKey, String> {
+    let mut characters = word.chars();
+
+    match (word, characters.next(), characters.next()) {
+        ("up", ..) => Ok(InputKey::Up),
+        ("down", ..) => Ok(InputKey::Down),
+        ("left", ..) => Ok(InputKey::Left),
+        ("right", ..) => Ok(InputKey::Right),
+        ("enter", ..) => Ok(InputKey::Enter),
+        ("esc", ..) => Ok(InputKey::Esc),
+        ("backspace", ..) => Ok(InputKey::Backspace),
+        ("space", ..) => Ok(InputKey::Char(' ')),
+        (_, Some(character), None) => Ok(InputKey::Char(character)),
+        _ => Err(format!("{} is not a key", word)),
+    }
+}
+
+fn arrow_for(command: Command) -> Option<InputKey> {
+    match command {
+        Command::Up => Some(InputKey::Up),
+        Command::Down => Some(InputKey::Down),
+        Command::Left => Some(InputKey::Left),
+        Command::Right => Some(InputKey::Right),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(text: &str) -> Bindings {
+        text.parse().unwrap()
+    }
+
+    #[test]
+    fn an_empty_file_uses_the_default_keys() {
+        let bindings = parse("");
+        assert_eq!(bindings.command(InputKey::Enter), Some(Command::Place));
+        assert_eq!(bindings.command(InputKey::Char('h')), Some(Command::Hard));
+        assert_eq!(bindings.digit_layout, DigitLayout::Numpad);
+    }
+
+    #[test]
+    fn presets_move_the_keys_they_take_over_to_shifted_letters() {
+        let vim = parse("preset vim");
+        assert_eq!(vim.command(InputKey::Char('h')), Some(Command::Left));
+        assert_eq!(vim.command(InputKey::Char('H')), Some(Command::Hard));
+        assert_eq!(vim.command(InputKey::Left), Some(Command::Left));
+        assert_eq!(vim.key_name(Command::Hard), "Shift+H");
+
+        let wasd = parse("preset wasd");
+        assert_eq!(wasd.command(InputKey::Char('s')), Some(Command::Down));
+        assert_eq!(wasd.command(InputKey::Char('S')), Some(Command::Start));
+    }
+
+    #[test]
+    fn shifted_letters_fall_back_to_the_plain_letter() {
+        let bindings = parse("");
+        assert_eq!(bindings.command(InputKey::Char('S')), Some(Command::Start));
+        // Only letters with nothing bound to the shifted letter.
+        assert_eq!(
+            parse("preset vim").command(InputKey::Char('L')),
+            Some(Command::Load)
+        );
+    }
+
+    #[test]
+    fn binding_a_key_takes_it_away_from_its_old_command() {
+        let bindings = parse("preset vim\nhint q\nstart s space");
+        assert_eq!(bindings.command(InputKey::Char('q')), Some(Command::Hint));
+        assert_eq!(bindings.command(InputKey::Char('?')), None);
+        assert_eq!(bindings.command(InputKey::Char(' ')), Some(Command::Start));
+        assert_eq!(bindings.key_name(Command::Start), "S");
+
+        let bindings = parse("undo r");
+        assert_eq!(bindings.command(InputKey::Char('r')), Some(Command::Undo));
+        assert_eq!(bindings.key_name(Command::Redo), "-");
+    }
+
+    #[test]
+    fn digits_can_be_laid_out_like_a_phone() {
+        assert_eq!(parse("digits phone").digit_layout, DigitLayout::Phone);
+    }
+
+    #[test]
+    fn place_and_quit_have_to_keep_a_key() {
+        assert!("start enter".parse::<Bindings>().is_err());
+        assert!("hint esc".parse::<Bindings>().is_err());
+        assert!("start enter\nplace p".parse::<Bindings>().is_ok());
+    }
+
+    #[test]
+    fn bad_lines_are_rejected() {
+        for text in [
+            "preset emacs",
+            "preset",
+            "digits",
+            "digits rotary",
+            "jump 1",
+            "hint",
+            "hint ctrl",
+        ] {
+            assert!(text.parse::<Bindings>().is_err(), "{:?} was read", text);
+        }
+    }
+}
@@ -0,0 +1,635 @@
+// The board, the moves that can be made on it and how a game is won.
+use std::{
+    fmt::{self, Display},
+    str::FromStr,
+};
+
+use crate::parse::parse_number;
+
+pub const MAX_BOARD_SIZE: usize = 20;
+
+// The size of the board and how many tokens in a row it takes to win.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BoardRules {
+    pub rows: usize,
+    pub columns: usize,
+    pub win_length: usize,
+}
+
+impl BoardRules {
+    pub const CLASSIC: BoardRules = BoardRules {
+        rows: 3,
+        columns: 3,
+        win_length: 3,
+    };
+
+    // The boards that can be picked from the menu, in the order they are cycled through.
+    pub const PRESETS: [BoardRules; 4] = [
+        BoardRules::CLASSIC,
+        BoardRules {
+            rows: 4,
+            columns: 4,
+            win_length: 4,
+        },
+        BoardRules {
+            rows: 5,
+            columns: 5,
+            win_length: 4,
+        },
+        BoardRules {
+            rows: 15,
+            columns: 15,
+            win_length: 5,
+        },
+    ];
+
+    // Keeps boards to something that fits on the screen and can actually be won.
+    pub fn is_valid(&self) -> bool {
+        (1..=MAX_BOARD_SIZE).contains(&self.rows)
+            && (1..=MAX_BOARD_SIZE).contains(&self.columns)
+            && (1..=self.rows.max(self.columns)).contains(&self.win_length)
+    }
+
+    pub fn next_preset(&self) -> BoardRules {
+        let index = BoardRules::PRESETS
+            .iter()
+            .position(|rules| rules == self)
+            .map_or(0, |index| (index + 1) % BoardRules::PRESETS.len());
+
+        BoardRules::PRESETS[index]
+    }
+}
+
+impl Display for BoardRules {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}x{}, {} in a row",
+            self.rows, self.columns, self.win_length
+        )
+    }
+}
+
+// The cells always match the rules, so they can only be changed one at a time through set_cell.
+#[derive(Clone, Debug)]
+pub struct BoardState {
+    rules: BoardRules,
+    cells: Vec<Vec<BoardCell>>,
+}
+
+impl Default for BoardState {
+    fn default() -> BoardState {
+        BoardState::new(BoardRules::CLASSIC)
+    }
+}
+
+impl BoardState {
+    pub fn new(rules: BoardRules) -> BoardState {
+        BoardState {
+            rules,
+            cells: vec![vec![BoardCell::Empty; rules.columns]; rules.rows],
+        }
+    }
+
+    pub fn rules(&self) -> BoardRules {
+        self.rules
+    }
+
+    // Every cell, row by row from the bottom of the board up.
+    pub fn cells(&self) -> &[Vec<BoardCell>] {
+        &self.cells
+    }
+
+    pub fn centre(&self) -> (usize, usize) {
+        (self.rules.rows / 2, self.rules.columns / 2)
+    }
+
+    pub fn cell(&self, (row, column): (usize, usize)) -> BoardCell {
+        self.cells[row][column]
+    }
+
+    pub fn set_cell(&mut self, (row, column): (usize, usize), cell: BoardCell) {
+        self.cells[row][column] = cell;
+    }
+
+    pub fn empty_cells(&self) -> Vec<(usize, usize)> {
+        self.positions()
+            .filter(|position| matches!(self.cell(*position), BoardCell::Empty))
+            .collect()
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.positions()
+            .all(|position| matches!(self.cell(position), BoardCell::Occupied(_)))
+    }
+
+    // Every [row][column] position on the board, starting from the bottom left cell.
+    pub fn positions(&self) -> impl Iterator<Item = (usize, usize)> {
+        let columns = self.rules.columns;
+        (0..self.rules.rows).flat_map(move |row| (0..columns).map(move |column| (row, column)))
+    }
+
+    pub fn try_place_token(
+        &mut self,
+        (row, column): (usize, usize),
+        player: Player,
+    ) -> PlaceTokenResult {
+        if row >= self.rules.rows || column >= self.rules.columns {
+            return PlaceTokenResult::Error;
+        }
+
+        match self.cell((row, column)) {
+            BoardCell::Empty => {
+                self.set_cell((row, column), BoardCell::Occupied(player));
+
+                match self.check_for_winner() {
+                    Some(player) => PlaceTokenResult::SuccessWithWinner(player),
+                    None => PlaceTokenResult::Success,
+                }
+            }
+            BoardCell::Occupied(player) => PlaceTokenResult::Occupied(player),
+        }
+    }
+
+    pub fn check_for_winner(&self) -> Option<Player> {
+        self.positions()
+            .find(|position| self.is_winning_move(*position))
+            .and_then(|position| match self.cell(position) {
+                BoardCell::Occupied(player) => Some(player),
+                BoardCell::Empty => None,
+            })
+    }
+
+    // Whether the token in the cell is part of a complete line.
+    pub fn is_winning_move(&self, position: (usize, usize)) -> bool {
+        match self.cell(position) {
+            BoardCell::Occupied(player) => self.completes_line(position, player),
+            BoardCell::Empty => false,
+        }
+    }
+
+    // Whether the player would have a complete line through the cell if they had a token in it.
+    pub fn completes_line(&self, position: (usize, usize), player: Player) -> bool {
+        self.completed_line(position, player).is_some()
+    }
+
+    // Which way the line the player would complete by having a token in the cell runs, if there is
+    // one. Across is (0, 1), up is (1, 0) and the diagonals are (1, 1) and (1, -1).
+    pub fn completed_line(
+        &self,
+        (row, column): (usize, usize),
+        player: Player,
+    ) -> Option<(isize, isize)> {
+        DIRECTIONS
+            .iter()
+            .find(|(row_step, column_step)| {
+                let forwards =
+                    self.count_in_direction((row, column), (*row_step, *column_step), player);
+                let backwards =
+                    self.count_in_direction((row, column), (-row_step, -column_step), player);
+
+                1 + forwards + backwards >= self.rules.win_length
+            })
+            .copied()
+    }
+
+    // Counts the player's tokens in an unbroken run from the cell, not including the cell itself.
+    fn count_in_direction(
+        &self,
+        position: (usize, usize),
+        (row_step, column_step): (isize, isize),
+        player: Player,
+    ) -> usize {
+        let mut count = 0;
+        let mut position = position;
+
+        while let Some(next_position) = self.step(position, (row_step, column_step)) {
+            match self.cell(next_position) {
+                BoardCell::Occupied(cell_player) if cell_player == player => count += 1,
+                _ => break,
+            }
+            position = next_position;
+        }
+
+        count
+    }
+
+    fn step(
+        &self,
+        (row, column): (usize, usize),
+        (row_step, column_step): (isize, isize),
+    ) -> Option<(usize, usize)> {
+        let row = row.checked_add_signed(row_step)?;
+        let column = column.checked_add_signed(column_step)?;
+
+        if row < self.rules.rows && column < self.rules.columns {
+            Some((row, column))
+        } else {
+            None
+        }
+    }
+
+    // Whether the player has a complete line anywhere on the board.
+    pub fn has_line(&self, player: Player) -> bool {
+        self.positions().any(|position| match self.cell(position) {
+            BoardCell::Occupied(cell_player) if cell_player == player => {
+                self.completes_line(position, player)
+            }
+            _ => false,
+        })
+    }
+
+    // The lines that have been completed, each with every cell of the unbroken run it's part of. More
+    // than one can be completed by the same move, and a run longer than win_length counts once.
+    pub fn completed_lines(&self) -> Vec<(Player, Vec<(usize, usize)>)> {
+        self.positions()
+            .flat_map(|start| DIRECTIONS.iter().map(move |direction| (start, *direction)))
+            .filter_map(|(start, (row_step, column_step))| {
+                let player = match self.cell(start) {
+                    BoardCell::Occupied(player) => player,
+                    BoardCell::Empty => return None,
+                };
+                // Only count each run from its first cell.
+                if let Some(previous) = self.step(start, (-row_step, -column_step)) {
+                    if self.cell(previous) == BoardCell::Occupied(player) {
+                        return None;
+                    }
+                }
+
+                let mut line = vec![start];
+                while let Some(next) = self.step(*line.last().unwrap(), (row_step, column_step)) {
+                    if self.cell(next) != BoardCell::Occupied(player) {
+                        break;
+                    }
+                    line.push(next);
+                }
+
+                if line.len() >= self.rules.win_length {
+                    Some((player, line))
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    // Every run of win_length cells in a straight line, across, up and along both diagonals.
+    pub fn winning_lines(&self) -> Vec<Vec<(usize, usize)>> {
+        let win_length = self.rules.win_length;
+
+        self.positions()
+            .flat_map(|start| {
+                DIRECTIONS.iter().filter_map(move |direction| {
+                    let mut line = vec![start];
+                    while line.len() < win_length {
+                        line.push(self.step(*line.last().unwrap(), *direction)?);
+                    }
+                    Some(line)
+                })
+            })
+            .collect()
+    }
+}
+
+// Across, up, and the two diagonals. Lines are checked in both directions so these cover them all.
+const DIRECTIONS: [(isize, isize); 4] = [(0, 1), (1, 0), (1, 1), (1, -1)];
+
+// A board and whose turn it is, written on one line as the rows from the top down separated by /,
+// then the side to move. `xo./.x./o.. x` is the classic board with x to move. The win length comes
+// last, and can be left off when it's the length of the shorter side of the board, so
+// `...../...../..x../...../..... o 4` is a 5x5 board with 4 in a row.
+#[derive(Clone, Debug)]
+pub struct Position {
+    pub board_state: BoardState,
+    pub to_move: Player,
+}
+
+impl Position {
+    // An empty board with x to move.
+    pub fn new(rules: BoardRules) -> Position {
+        Position {
+            board_state: BoardState::new(rules),
+            to_move: Player::Cross,
+        }
+    }
+}
+
+impl Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let BoardRules {
+            rows,
+            columns,
+            win_length,
+        } = self.board_state.rules();
+
+        let rows: Vec<String> = (0..rows)
+            .rev()
+            .map(|row| {
+                (0..columns)
+                    .map(|column| match self.board_state.cell((row, column)) {
+                        BoardCell::Empty => '.',
+                        BoardCell::Occupied(player) => player.token(),
+                    })
+                    .collect()
+            })
+            .collect();
+
+        write!(f, "{} {}", rows.join("/"), self.to_move.token())?;
+        if win_length != default_win_length(self.board_state.rules()) {
+            write!(f, " {}", win_length)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for Position {
+    type Err = String;
+
+    // Only positions that could come up in a real game are accepted. Either side can have gone
+    // first, so the counts can be level with either side to move.
+    fn from_str(text: &str) -> Result<Position, String> {
+        let words: Vec<&str> = text.split_whitespace().collect();
+        let (board, to_move, win_length) = match words.as_slice() {
+            [board, to_move] => (*board, *to_move, None),
+            [board, to_move, win_length] => (*board, *to_move, Some(*win_length)),
+            _ => {
+                return Err(String::from(
+                    "a position is the board, the side to move and an optional win length",
+                ))
+            }
+        };
+
+        let to_move = match to_move {
+            "x" => Player::Cross,
+            "o" => Player::Nought,
+            _ => return Err(format!("{} is not x or o", to_move)),
+        };
+
+        let cells = board
+            .split('/')
+            .rev()
+            .map(|row| {
+                row.chars()
+                    .map(|token| match token {
+                        '.' => Ok(BoardCell::Empty),
+                        'x' => Ok(BoardCell::Occupied(Player::Cross)),
+                        'o' => Ok(BoardCell::Occupied(Player::Nought)),
+                        _ => Err(format!("{} is not x, o or .", token)),
+                    })
+                    .collect::<Result<Vec<BoardCell>, String>>()
+            })
+            .collect::<Result<Vec<Vec<BoardCell>>, String>>()?;
+
+        let columns = cells[0].len();
+        if cells.iter().any(|row| row.len() != columns) {
+            return Err(String::from("the rows aren't all the same length"));
+        }
+
+        let mut rules = BoardRules {
+            rows: cells.len(),
+            columns,
+            win_length: 0,
+        };
+        rules.win_length = match win_length {
+            Some(win_length) => parse_number(win_length)?,
+            None => default_win_length(rules),
+        };
+        if !rules.is_valid() {
+            return Err(format!("{} isn't a valid board", rules));
+        }
+
+        let board_state = BoardState { rules, cells };
+        let count = |player| {
+            board_state
+                .positions()
+                .filter(|position| matches!(board_state.cell(*position), BoardCell::Occupied(cell_player) if cell_player == player))
+                .count()
+        };
+        let (crosses, noughts) = (count(Player::Cross), count(Player::Nought));
+
+        if crosses > noughts + 1 {
+            return Err(String::from("there are too many x's"));
+        }
+        if noughts > crosses + 1 {
+            return Err(String::from("there are too many o's"));
+        }
+        // Whoever has more tokens just moved.
+        if (crosses > noughts && to_move == Player::Cross)
+            || (noughts > crosses && to_move == Player::Nought)
+        {
+            return Err(format!("it can't be {}'s turn", to_move.token()));
+        }
+
+        match (
+            board_state.has_line(Player::Cross),
+            board_state.has_line(Player::Nought),
+        ) {
+            (true, true) => Err(String::from("both sides have won")),
+            (true, false) if to_move == Player::Cross => {
+                Err(String::from("x has won but it's x's turn"))
+            }
+            (false, true) if to_move == Player::Nought => {
+                Err(String::from("o has won but it's o's turn"))
+            }
+            _ => Ok(Position {
+                board_state,
+                to_move,
+            }),
+        }
+    }
+}
+
+// The win length a position gets when it doesn't give one.
+fn default_win_length(rules: BoardRules) -> usize {
+    rules.rows.min(rules.columns)
+}
+
+pub enum PlaceTokenResult {
+    SuccessWithWinner(Player),
+    Success,
+    // The cell already has a token in it, played by the player given.
+    Occupied(Player),
+    Error,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BoardCell {
+    Empty,
+    Occupied(Player),
+}
+
+impl Display for BoardCell {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let board_cell = match &self {
+            BoardCell::Empty => "*",
+            BoardCell::Occupied(Player::Nought) => "o",
+            BoardCell::Occupied(Player::Cross) => "x",
+        };
+
+        // Uses pad so the cell can be centred with a width in the format string.
+        f.pad(board_cell)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Player {
+    Cross,
+    Nought,
+}
+
+impl Player {
+    pub fn index(&self) -> usize {
+        match self {
+            Player::Cross => 0,
+            Player::Nought => 1,
+        }
+    }
+
+    // The token each side plays with. Cross always goes first on an empty board in a position.
+    pub fn token(&self) -> char {
+        match self {
+            Player::Cross => 'x',
+            Player::Nought => 'o',
+        }
+    }
+
+    pub fn opponent(&self) -> Player {
+        match self {
+            Player::Cross => Player::Nought,
+            Player::Nought => Player::Cross,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Move {
+    pub player: Player,
+    pub row: usize,
+    pub column: usize,
+    // Counts up from 1 for the first token placed in the game.
+    pub turn: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(text: &str) -> Result<Position, String> {
+        text.parse()
+    }
+
+    #[test]
+    fn positions_round_trip() {
+        for text in [
+            ".../.../... x",
+            "xo./.x./o.. x",
+            "xo./.x./oo. x",
+            "x../.o./... x",
+            "...../...../..x../...../..... o 4",
+            "..../..../.... x 2",
+        ] {
+            assert_eq!(parse(text).unwrap().to_string(), text);
+        }
+    }
+
+    #[test]
+    fn rows_are_read_from_the_top_down() {
+        let position = parse("x../.../..o o").unwrap();
+        assert_eq!(
+            position.board_state.cell((2, 0)),
+            BoardCell::Occupied(Player::Cross)
+        );
+        assert_eq!(
+            position.board_state.cell((0, 2)),
+            BoardCell::Occupied(Player::Nought)
+        );
+        assert_eq!(position.to_move, Player::Nought);
+    }
+
+    #[test]
+    fn default_win_length_is_the_shorter_side() {
+        let position = parse(".../.../.../... x").unwrap();
+        assert_eq!(
+            position.board_state.rules(),
+            BoardRules {
+                rows: 4,
+                columns: 3,
+                win_length: 3,
+            }
+        );
+        assert_eq!(position.to_string(), ".../.../.../... x");
+    }
+
+    #[test]
+    fn non_default_win_length_is_kept() {
+        let position = parse("...../...../...../...../..... x 4").unwrap();
+        assert_eq!(position.board_state.rules().win_length, 4);
+        assert_eq!(position.to_string(), "...../...../...../...../..... x 4");
+    }
+
+    #[test]
+    fn too_many_crosses() {
+        assert_eq!(
+            parse("xx./.../... o").unwrap_err(),
+            "there are too many x's"
+        );
+    }
+
+    #[test]
+    fn too_many_noughts() {
+        assert_eq!(
+            parse("oo./.../... x").unwrap_err(),
+            "there are too many o's"
+        );
+    }
+
+    #[test]
+    fn both_sides_won() {
+        assert_eq!(parse("xxx/ooo/... x").unwrap_err(), "both sides have won");
+    }
+
+    #[test]
+    fn wrong_side_to_move() {
+        assert_eq!(parse("x../.../... x").unwrap_err(), "it can't be x's turn");
+        assert_eq!(parse("o../.../... o").unwrap_err(), "it can't be o's turn");
+        assert_eq!(parse("xxx/oo./... x").unwrap_err(), "it can't be x's turn");
+        assert_eq!(
+            parse("xx./ooo/x.. o").unwrap_err(),
+            "o has won but it's o's turn"
+        );
+    }
+
+    #[test]
+    fn one_move_can_complete_two_lines() {
+        let mut board_state = BoardState::default();
+        for cell in [(2, 1), (2, 2), (1, 0), (0, 0)] {
+            board_state.set_cell(cell, BoardCell::Occupied(Player::Cross));
+        }
+        assert!(board_state.completed_lines().is_empty());
+
+        board_state.set_cell((2, 0), BoardCell::Occupied(Player::Cross));
+        let lines = board_state.completed_lines();
+        assert_eq!(lines.len(), 2);
+        assert!(lines.contains(&(Player::Cross, vec![(2, 0), (2, 1), (2, 2)])));
+        assert!(lines.contains(&(Player::Cross, vec![(0, 0), (1, 0), (2, 0)])));
+    }
+
+    #[test]
+    fn long_runs_count_as_one_line() {
+        let position = parse("..../xxxx/.o../oo.. o 3").unwrap();
+        assert_eq!(
+            position.board_state.completed_lines(),
+            [(Player::Cross, vec![(2, 0), (2, 1), (2, 2), (2, 3)])]
+        );
+    }
+
+    #[test]
+    fn badly_formed_positions() {
+        assert!(parse(".../.../...").is_err());
+        assert!(parse(".../..../... x").is_err());
+        assert!(parse(".../.z./... x").is_err());
+        assert!(parse(".../.../... y").is_err());
+        assert!(parse(".../.../... x 4").is_err());
+    }
+}
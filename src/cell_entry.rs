@@ -0,0 +1,148 @@
+// Picking a cell without moving the cursor to it first. On the 3x3 board the digits 1 to 9 stand for
+// the cells, laid out like a numpad with 1 in the bottom left, or like a phone with 1 in the top left.
+// On any board a cell can be typed as a column letter and a row number, like b3, where a is the left
+// column and 1 is the bottom row. That matches the [row][column] order in app.rs, just counting
+// from 1.
+use std::str::FromStr;
+
+use noughts_and_crosses::board::BoardRules;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DigitLayout {
+    Numpad,
+    Phone,
+}
+
+impl FromStr for DigitLayout {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<DigitLayout, String> {
+        match name {
+            "numpad" => Ok(DigitLayout::Numpad),
+            "phone" => Ok(DigitLayout::Phone),
+            _ => Err(format!("{} is not numpad or phone", name)),
+        }
+    }
+}
+
+// None for anything but 1 to 9 on the 3x3 board.
+pub fn cell_for_digit(
+    digit: u32,
+    layout: DigitLayout,
+    rules: BoardRules,
+) -> Option<(usize, usize)> {
+    if rules.rows != 3 || rules.columns != 3 || !(1..=9).contains(&digit) {
+        return None;
+    }
+
+    let index = digit as usize - 1;
+    let row = match layout {
+        DigitLayout::Numpad => index / 3,
+        DigitLayout::Phone => 2 - index / 3,
+    };
+
+    Some((row, index % 3))
+}
+
+pub fn parse_cell_name(text: &str, rules: BoardRules) -> Result<(usize, usize), String> {
+    let text = text.trim().to_lowercase();
+    let mut characters = text.chars();
+
+    let column = match characters.next() {
+        Some(letter @ 'a'..='z') => letter as usize - 'a' as usize,
+        _ => return Err(format!("{} doesn't start with a column letter", text)),
+    };
+    let row = match characters.as_str().parse::<usize>() {
+        Ok(number) if number >= 1 => number - 1,
+        _ => return Err(format!("{} doesn't end with a row number", text)),
+    };
+
+    if row >= rules.rows || column >= rules.columns {
+        return Err(format!(
+            "{} isn't on a {}x{} board",
+            text, rules.rows, rules.columns
+        ));
+    }
+
+    Ok((row, column))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WIDE: BoardRules = BoardRules {
+        rows: 10,
+        columns: 12,
+        win_length: 5,
+    };
+
+    #[test]
+    fn numpad_digits_start_in_the_bottom_left() {
+        let cell = |digit| cell_for_digit(digit, DigitLayout::Numpad, BoardRules::CLASSIC);
+
+        assert_eq!(cell(1), Some((0, 0)));
+        assert_eq!(cell(3), Some((0, 2)));
+        assert_eq!(cell(5), Some((1, 1)));
+        assert_eq!(cell(7), Some((2, 0)));
+        assert_eq!(cell(9), Some((2, 2)));
+    }
+
+    #[test]
+    fn phone_digits_start_in_the_top_left() {
+        let cell = |digit| cell_for_digit(digit, DigitLayout::Phone, BoardRules::CLASSIC);
+
+        assert_eq!(cell(1), Some((2, 0)));
+        assert_eq!(cell(3), Some((2, 2)));
+        assert_eq!(cell(5), Some((1, 1)));
+        assert_eq!(cell(7), Some((0, 0)));
+        assert_eq!(cell(9), Some((0, 2)));
+    }
+
+    #[test]
+    fn digits_only_work_on_the_classic_board() {
+        assert_eq!(
+            cell_for_digit(0, DigitLayout::Numpad, BoardRules::CLASSIC),
+            None
+        );
+        assert_eq!(cell_for_digit(5, DigitLayout::Numpad, WIDE), None);
+    }
+
+    #[test]
+    fn cell_names_count_from_the_bottom_left() {
+        assert_eq!(parse_cell_name("a1", BoardRules::CLASSIC), Ok((0, 0)));
+        assert_eq!(parse_cell_name("b3", BoardRules::CLASSIC), Ok((2, 1)));
+        assert_eq!(parse_cell_name(" C2 ", BoardRules::CLASSIC), Ok((1, 2)));
+    }
+
+    #[test]
+    fn cell_names_on_wide_boards() {
+        assert_eq!(parse_cell_name("l1", WIDE), Ok((0, 11)));
+        assert_eq!(parse_cell_name("j10", WIDE), Ok((9, 9)));
+    }
+
+    #[test]
+    fn cells_off_the_board_are_rejected() {
+        for text in ["d1", "a4", "a0"] {
+            assert!(
+                parse_cell_name(text, BoardRules::CLASSIC).is_err(),
+                "{}",
+                text
+            );
+        }
+        for text in ["m1", "a11"] {
+            assert!(parse_cell_name(text, WIDE).is_err(), "{}", text);
+        }
+    }
+
+    #[test]
+    fn badly_formed_cell_names_are_rejected() {
+        for text in ["", "1a", "a", "aa1", "a1b", "a-1", "?2"] {
+            assert!(
+                parse_cell_name(text, BoardRules::CLASSIC).is_err(),
+                "{}",
+                text
+            );
+        }
+    }
+}
@@ -0,0 +1,273 @@
+// The subcommands that don't need the terminal UI, so games can be looked at and the computer tested
+// from scripts. Everything is printed to stdout and problems come back as errors.
+use std::{
+    io::{self, ErrorKind},
+    path::PathBuf,
+    thread,
+};
+
+use noughts_and_crosses::{
+    board::{BoardCell, BoardRules, BoardState, Position},
+    parse::parse_number,
+    strategy::{self, Level, Outcome},
+};
+
+use crate::{
+    app::App,
+    config,
+    record::{GameRecord, RecordResult},
+    replay::Replay,
+    selfplay::{self, Estimate},
+    stats::Stats,
+};
+
+pub const USAGE: &str = "usage: noughts_and_crosses [command]
+
+commands:
+  play [--<setting> <value>]... [host [port] | join <address>[:port]]
+                      Play in the terminal. This is the default, see the README for the settings.
+  engine              Play over stdin and stdout, see engine.rs.
+  solve <position>    Print the value of a position for the side to move and the best moves.
+  selfplay [--games <n>] [--level <level>] [--vs <level>] [--board <board>] [--threads <n>]
+                      Play two levels against each other and print the results. A level can have
+                      a mistake rate, like medium:25 or adaptive:10.
+  replay [file]       Print every move of a saved game. Defaults to the game saved with W.
+  stats               Print the results kept against each opponent and level.
+  help                Print this.";
+
+// Prints `value <win|loss> <moves>`, `value draw` or `value unknown`, then `best <row> <column>` for
+// each of the best cells.
+pub fn solve(args: &[String]) -> io::Result<()> {
+    let position: Position = args.join(" ").parse().map_err(invalid_input)?;
+    let board_state = &position.board_state;
+
+    if board_state.check_for_winner().is_some() || board_state.is_full() {
+        return Err(invalid_input(String::from("the game is over")));
+    }
+
+    let (outcome, cells) = strategy::best_cells(board_state, position.to_move)
+        .ok_or_else(|| invalid_input(String::from("there's nowhere to play")))?;
+
+    match outcome {
+        Outcome::Win(moves) => println!("value win {}", moves),
+        Outcome::Loss(moves) => println!("value loss {}", moves),
+        Outcome::Draw => println!("value draw"),
+        Outcome::Unknown => println!("value unknown"),
+    }
+    for (row, column) in cells {
+        println!("best {} {}", row, column);
+    }
+
+    Ok(())
+}
+
+// Prints a table of wins, draws and losses split by who went first, then each rate with its 95%
+// confidence interval and whether either side is clearly stronger.
+pub fn selfplay(args: &[String]) -> io::Result<()> {
+    let mut games = 1000;
+    let mut level = Level::Hard;
+    let mut opponent = Level::Easy;
+    let mut rules = BoardRules::CLASSIC;
+    let mut threads = thread::available_parallelism().map_or(1, |threads| threads.get());
+
+    for (option, value) in options(args)? {
+        match option {
+            "games" => games = parse_number(value).map_err(invalid_input)?,
+            "level" => level = value.parse().map_err(invalid_input)?,
+            "vs" => opponent = value.parse().map_err(invalid_input)?,
+            "board" => rules = config::parse_board(value).map_err(invalid_input)?,
+            "threads" => threads = parse_number(value).map_err(invalid_input)?,
+            _ => {
+                return Err(invalid_input(format!(
+                    "--{} is not a selfplay option",
+                    option
+                )))
+            }
+        }
+    }
+
+    if games == 0 {
+        return Err(invalid_input(String::from(
+            "--games needs to be at least 1",
+        )));
+    }
+
+    let results = selfplay::play_games(rules, [level, opponent], games, threads);
+    let total = results.total();
+    let (first, second) = (results.going_first, results.going_second);
+    let first_name = selfplay::level_name(level);
+    let second_name = selfplay::level_name(opponent);
+
+    println!(
+        "{} vs {} on {}, {} games taking turns to go first",
+        first_name, second_name, rules, games
+    );
+    println!();
+    println!(
+        "{:<20}{:>20}{:>20}{:>10}",
+        "",
+        format!("{} first", first_name),
+        format!("{} first", second_name),
+        "total"
+    );
+    for (label, first_count, second_count, total_count) in [
+        (
+            format!("{} won", first_name),
+            first.won,
+            second.won,
+            total.won,
+        ),
+        (
+            String::from("drawn"),
+            first.drawn,
+            second.drawn,
+            total.drawn,
+        ),
+        (
+            format!("{} won", second_name),
+            first.lost,
+            second.lost,
+            total.lost,
+        ),
+    ] {
+        println!(
+            "{:<20}{:>20}{:>20}{:>10}",
+            label, first_count, second_count, total_count
+        );
+    }
+
+    let score = total.score();
+    println!();
+    println!(
+        "{:<20}{}",
+        format!("{} won", first_name),
+        Estimate::rate(total.won, games)
+    );
+    println!("{:<20}{}", "drawn", Estimate::rate(total.drawn, games));
+    println!(
+        "{:<20}{}",
+        format!("{} won", second_name),
+        Estimate::rate(total.lost, games)
+    );
+    println!("{:<20}{}", format!("{} score", first_name), score);
+    println!();
+    if score.low > 0.5 {
+        println!("{} is stronger.", first_name);
+    } else if score.high < 0.5 {
+        println!("{} is stronger.", second_name);
+    } else {
+        println!("Neither side is clearly stronger, try more games.");
+    }
+
+    Ok(())
+}
+
+pub fn replay(args: &[String]) -> io::Result<()> {
+    let path = match args {
+        [] => GameRecord::path(),
+        [path] => PathBuf::from(path),
+        _ => return Err(invalid_input(String::from("replay takes one file"))),
+    };
+    let record = GameRecord::load(&path)?;
+    // Checks the moves before any of them are printed.
+    App::default()
+        .game_state
+        .replay_record(&record)
+        .map_err(|error| io::Error::new(ErrorKind::InvalidData, error))?;
+
+    println!("{}, {} moves", record.rules, record.moves.len());
+    let mut replay = Replay::new(record.clone());
+    print_chat(&replay);
+    for turn in 1..=record.moves.len() {
+        replay.forward();
+        let (row, column) = replay.last_move().unwrap_or_default();
+        println!();
+        println!(
+            "{}. {} {},{}",
+            turn,
+            replay.board_state.cell((row, column)),
+            row,
+            column
+        );
+        print_board(&replay.board_state);
+        print_chat(&replay);
+    }
+
+    println!();
+    match record.result {
+        RecordResult::Winner(player) => println!("{} won", player.token()),
+        RecordResult::Draw => println!("Draw"),
+        RecordResult::Unfinished => println!("Unfinished"),
+    }
+
+    Ok(())
+}
+
+pub fn stats(args: &[String]) -> io::Result<()> {
+    if !args.is_empty() {
+        return Err(invalid_input(String::from(
+            "stats doesn't take any arguments",
+        )));
+    }
+
+    let stats = Stats::load(&Stats::path())?;
+    println!(
+        "{:<10}{:<12}{:>8}{:>6}{:>6}{:>8}{:>10}",
+        "opponent", "level", "played", "won", "lost", "drawn", "win rate"
+    );
+    for (character, level, totals) in stats.entries() {
+        let win_rate = totals
+            .win_rate()
+            .map_or(String::from("-"), |win_rate| format!("{}%", win_rate));
+        println!(
+            "{:<10}{:<12}{:>8}{:>6}{:>6}{:>8}{:>10}",
+            character,
+            level,
+            totals.played(),
+            totals.won,
+            totals.lost,
+            totals.drawn,
+            win_rate
+        );
+    }
+
+    Ok(())
+}
+
+// The top row first, like the board on screen.
+fn print_board(board_state: &BoardState) {
+    for cells in board_state.cells().iter().rev() {
+        let row: Vec<String> = cells
+            .iter()
+            .map(|cell| match cell {
+                BoardCell::Empty => String::from("."),
+                BoardCell::Occupied(player) => player.token().to_string(),
+            })
+            .collect();
+        println!("  {}", row.join(" "));
+    }
+}
+
+fn print_chat(replay: &Replay) {
+    for line in replay.chat() {
+        println!("  {}", line);
+    }
+}
+
+// Pairs up `--<option> <value>` arguments.
+fn options(args: &[String]) -> io::Result<Vec<(&str, &str)>> {
+    args.chunks(2)
+        .map(|pair| match pair {
+            [option, value] if option.starts_with("--") => Ok((&option[2..], value.as_str())),
+            [option, ..] if option.starts_with("--") => {
+                Err(invalid_input(format!("{} needs a value", option)))
+            }
+            [argument, ..] => Err(invalid_input(format!("unexpected {}", argument))),
+            [] => unreachable!(),
+        })
+        .collect()
+}
+
+fn invalid_input(error: String) -> io::Error {
+    io::Error::new(ErrorKind::InvalidInput, error)
+}
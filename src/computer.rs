@@ -1,19 +1,37 @@
-use crate::{
-    app::{BoardCell, BoardCellState, BoardState, GameState, Level, Player},
-    Event,
-};
+use crate::{app::GameState, game_match::MatchPoint, Event};
+use noughts_and_crosses::{board::Player, strategy};
 use rand::Rng;
+use std::time::Duration;
 use std::{
     fmt::{self, Display},
-    sync::mpsc::{self, Receiver, Sender},
+    str::FromStr,
+    sync::mpsc::{self, Receiver, RecvTimeoutError, Sender},
 };
-use std::{thread, time::Duration};
 
 // Events sent
 pub enum Action {
-    Chat(String),
-    PlaceToken(usize, usize),
-    PlaceTokenError,
+    Chat(TurnId, String),
+    PlaceToken(TurnId, usize, usize),
+    PlaceTokenError(TurnId),
+}
+
+impl Action {
+    pub fn turn_id(&self) -> TurnId {
+        match self {
+            Action::Chat(turn_id, _)
+            | Action::PlaceToken(turn_id, _, _)
+            | Action::PlaceTokenError(turn_id) => *turn_id,
+        }
+    }
+}
+
+// Identifies the game and turn that a trigger was sent for. Every action the computer sends back
+// carries the id of the trigger it's responding to, so the app can drop anything that arrives
+// after the game has been reset or the turn has been undone.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TurnId {
+    pub game: u64,
+    pub turn: u64,
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -29,6 +47,14 @@ impl Character {
             Character::SpeedySteve => String::from("Speedy Steve"),
         }
     }
+
+    // Dave takes his time, Steve rushes and slips up more often.
+    pub fn default_mistake_percentage(&self) -> u8 {
+        match self {
+            Character::ChattyDave => 25,
+            Character::SpeedySteve => 40,
+        }
+    }
 }
 
 impl Display for Character {
@@ -42,212 +68,244 @@ impl Display for Character {
     }
 }
 
+impl FromStr for Character {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Character, String> {
+        match name.to_lowercase().as_str() {
+            "dave" => Ok(Character::ChattyDave),
+            "steve" => Ok(Character::SpeedySteve),
+            _ => Err(format!("{} is not dave or steve", name)),
+        }
+    }
+}
+
 // Events received
 pub enum Trigger {
-    ComputersTurn(GameState),
+    ComputersTurn(TurnId, GameState),
     // TODO - think of a better name
-    ComputersTurnFirst,
-    Loser,
-    Winner,
-    Draw,
+    ComputersTurnFirst(TurnId, GameState),
+    Loser(TurnId),
+    // Holds the key for a new game, which the computer suggests pressing.
+    Winner(TurnId, String),
+    Draw(TurnId),
+    // Sent at the start of a game in a match when either side is one win away from taking it.
+    MatchPoint(TurnId, Character, MatchPoint),
+    // Sent instead of Winner, Loser or Draw when the game decided the match. Holds who won the
+    // match, or None if it was tied.
+    MatchOver(TurnId, Character, Option<Player>),
+    // Stops whatever the computer is doing. Sent when the turn it's working on no longer exists.
+    Cancel,
+}
+
+// Why the computer stopped waiting early.
+enum Interruption {
+    Trigger(Box<Trigger>),
+    Disconnected,
 }
 
-pub fn start(sender: Sender<Event>) -> Sender<Trigger> {
+// The think time scales every wait, so 0 has the computer move and chat straight away.
+pub fn start(sender: Sender<Event>, think_time: f64) -> Sender<Trigger> {
     let (computer_sender, computer_receiver): (Sender<Trigger>, Receiver<Trigger>) =
         mpsc::channel();
 
-    std::thread::spawn(move || loop {
-        match computer_receiver.recv().unwrap() {
-            Trigger::ComputersTurn(game_state) => {
-                match game_state.computer_character {
-                    Character::ChattyDave => {
-                        wait_in_seconds(2);
-                        thinking(&sender);
-                        wait_in_seconds(5);
-                    }
-                    Character::SpeedySteve => {
-                        wait_in_seconds(1);
-                        send_chat_event("🤔", &sender);
-                        wait_in_seconds(2);
-                    }
-                }
+    std::thread::spawn(move || {
+        let mut next_trigger = None;
+
+        loop {
+            let trigger = match next_trigger.take() {
+                Some(trigger) => trigger,
+                None => match computer_receiver.recv() {
+                    Ok(trigger) => trigger,
+                    Err(_) => break,
+                },
+            };
+
+            // Any trigger that arrives while the computer is waiting replaces what it was doing.
+            match handle_trigger(trigger, &sender, &computer_receiver, think_time) {
+                Ok(()) => (),
+                Err(Interruption::Trigger(trigger)) => next_trigger = Some(*trigger),
+                Err(Interruption::Disconnected) => break,
+            }
+        }
+    });
 
-                match find_empty_cell(game_state) {
-                    Some((row, column)) => sender
-                        .send(Event::ComputerAction(Action::PlaceToken(row, column)))
-                        .unwrap(),
-                    None => sender
-                        .send(Event::ComputerAction(Action::PlaceTokenError))
-                        .unwrap(),
+    computer_sender
+}
+
+fn handle_trigger(
+    trigger: Trigger,
+    sender: &Sender<Event>,
+    receiver: &Receiver<Trigger>,
+    think_time: f64,
+) -> Result<(), Interruption> {
+    let wait_in_seconds = |seconds, receiver| wait_in_seconds(seconds, think_time, receiver);
+
+    match trigger {
+        Trigger::ComputersTurn(turn_id, game_state) => {
+            match game_state.computer_character {
+                Character::ChattyDave => {
+                    wait_in_seconds(2, receiver)?;
+                    thinking(turn_id, sender, receiver, think_time)?;
+                    wait_in_seconds(5, receiver)?;
+                }
+                Character::SpeedySteve => {
+                    wait_in_seconds(1, receiver)?;
+                    send_chat_event("🤔", turn_id, sender);
+                    wait_in_seconds(2, receiver)?;
                 }
             }
 
-            Trigger::Winner => {
-                wait_in_seconds(2);
-                send_chat_event("Winner, winner, chicken dinner 🏆", &sender);
-                wait_in_seconds(5);
-                send_chat_event(
-                    "Want to play again? Press N to clear the game board and I can beat you again.",
-                    &sender,
-                );
+            match find_empty_cell(game_state) {
+                Some((row, column)) => sender
+                    .send(Event::ComputerAction(Action::PlaceToken(
+                        turn_id, row, column,
+                    )))
+                    .unwrap(),
+                None => sender
+                    .send(Event::ComputerAction(Action::PlaceTokenError(turn_id)))
+                    .unwrap(),
             }
+        }
 
-            Trigger::Loser => {
-                send_chat_event("Nicely played 👏", &sender);
-            }
+        Trigger::Winner(turn_id, new_game_key) => {
+            wait_in_seconds(2, receiver)?;
+            send_chat_event("Winner, winner, chicken dinner 🏆", turn_id, sender);
+            wait_in_seconds(5, receiver)?;
+            send_chat_event(
+                &format!(
+                    "Want to play again? Press {} to clear the game board and I can beat you again.",
+                    new_game_key
+                ),
+                turn_id,
+                sender,
+            );
+        }
 
-            Trigger::Draw => {
-                send_chat_event("Looks like it's a draw. Want to play again?", &sender);
-            }
+        Trigger::Loser(turn_id) => {
+            send_chat_event("Nicely played 👏", turn_id, sender);
+        }
 
-            Trigger::ComputersTurnFirst => {
-                wait_in_seconds(3);
-                let place_token_in_centre_cell = Action::PlaceToken(1, 1);
+        Trigger::Draw(turn_id) => {
+            send_chat_event(
+                "Looks like it's a draw. Want to play again?",
+                turn_id,
+                sender,
+            );
+        }
 
-                sender
-                    .send(Event::ComputerAction(place_token_in_centre_cell))
-                    .unwrap();
+        Trigger::MatchPoint(turn_id, character, match_point) => {
+            let chat_message = match (character, match_point) {
+                (Character::ChattyDave, MatchPoint::User) => "Match point to you. No pressure 😅",
+                (Character::ChattyDave, MatchPoint::Computer) => {
+                    "One more win and the match is mine!"
+                }
+                (Character::ChattyDave, MatchPoint::Both) => "It all comes down to this one!",
+                (Character::SpeedySteve, MatchPoint::User) => "You need one more? Not happening.",
+                (Character::SpeedySteve, MatchPoint::Computer) => {
+                    "Match point. This'll be quick ⚡"
+                }
+                (Character::SpeedySteve, MatchPoint::Both) => "Decider. Winner takes all.",
+            };
+            send_chat_event(chat_message, turn_id, sender);
+        }
 
-                send_chat_event("Alright, you're up.", &sender);
-            }
+        Trigger::MatchOver(turn_id, character, winner) => {
+            wait_in_seconds(2, receiver)?;
+            let chat_message = match (character, winner) {
+                (Character::ChattyDave, Some(Player::Nought)) => {
+                    "That's the match! Good games though 🤝"
+                }
+                (Character::ChattyDave, Some(Player::Cross)) => {
+                    "Well played, the match is yours 🏆"
+                }
+                (Character::ChattyDave, None) => "All square! Let's call that one even 😊",
+                (Character::SpeedySteve, Some(Player::Nought)) => "Match over. Too easy 😎",
+                (Character::SpeedySteve, Some(Player::Cross)) => {
+                    "Ugh, you got me this time. Rematch?"
+                }
+                (Character::SpeedySteve, None) => "A tie? I'll take you next time.",
+            };
+            send_chat_event(chat_message, turn_id, sender);
         }
-    });
 
-    computer_sender
+        Trigger::ComputersTurnFirst(turn_id, game_state) => {
+            wait_in_seconds(3, receiver)?;
+            let (row, column) = game_state.board_state.centre();
+            let place_token_in_centre_cell = Action::PlaceToken(turn_id, row, column);
+
+            sender
+                .send(Event::ComputerAction(place_token_in_centre_cell))
+                .unwrap();
+
+            send_chat_event("Alright, you're up.", turn_id, sender);
+        }
+
+        Trigger::Cancel => (),
+    }
+
+    Ok(())
 }
 
-fn thinking(sender: &Sender<Event>) {
+fn thinking(
+    turn_id: TurnId,
+    sender: &Sender<Event>,
+    receiver: &Receiver<Trigger>,
+    think_time: f64,
+) -> Result<(), Interruption> {
+    let wait_in_seconds = |seconds, receiver| wait_in_seconds(seconds, think_time, receiver);
+
     match rand::thread_rng().gen_range(0..=10) {
         0..=3 => {
-            send_chat_event("🤔", sender);
+            send_chat_event("🤔", turn_id, sender);
         }
         4 | 5 => {
-            send_chat_event("Hmm this is tough.", sender);
+            send_chat_event("Hmm this is tough.", turn_id, sender);
         }
         6 | 7 => {
-            send_chat_event("BRB, just going to grab a coffee.", sender);
-            wait_in_seconds(6);
-            send_chat_event("Ok, back!", sender);
+            send_chat_event("BRB, just going to grab a coffee.", turn_id, sender);
+            wait_in_seconds(6, receiver)?;
+            send_chat_event("Ok, back!", turn_id, sender);
         }
         8 | 9 => {
-            send_chat_event("We should really go for a beer soon 🍺", sender);
-            wait_in_seconds(4);
-            send_chat_event("Oh, it's my turn! Let me think 🤔", sender);
+            send_chat_event("We should really go for a beer soon 🍺", turn_id, sender);
+            wait_in_seconds(4, receiver)?;
+            send_chat_event("Oh, it's my turn! Let me think 🤔", turn_id, sender);
         }
-        10 => send_chat_event("Really? You're going there 😂", sender),
+        10 => send_chat_event("Really? You're going there 😂", turn_id, sender),
         _ => {}
     }
+
+    Ok(())
 }
 
-fn wait_in_seconds(seconds: u64) {
-    thread::sleep(Duration::from_secs(seconds));
+// Waits for the given time unless another trigger arrives first, in which case the computer
+// gives up on what it was doing so the new trigger can be handled straight away.
+fn wait_in_seconds(
+    seconds: u64,
+    think_time: f64,
+    receiver: &Receiver<Trigger>,
+) -> Result<(), Interruption> {
+    match receiver.recv_timeout(Duration::from_secs_f64(seconds as f64 * think_time)) {
+        Ok(trigger) => Err(Interruption::Trigger(Box::new(trigger))),
+        Err(RecvTimeoutError::Timeout) => Ok(()),
+        Err(RecvTimeoutError::Disconnected) => Err(Interruption::Disconnected),
+    }
 }
 
-fn send_chat_event(chat_message: &str, sender: &Sender<Event>) {
+fn send_chat_event(chat_message: &str, turn_id: TurnId, sender: &Sender<Event>) {
     sender
-        .send(Event::ComputerAction(Action::Chat(String::from(
-            chat_message,
-        ))))
+        .send(Event::ComputerAction(Action::Chat(
+            turn_id,
+            String::from(chat_message),
+        )))
         .unwrap();
 }
 
 fn find_empty_cell(game_state: GameState) -> Option<(usize, usize)> {
-    match game_state.difficulty_level {
-        Level::Easy => find_any_empty_cell(game_state.board_state),
-        Level::Hard => find_best_empty_cell(game_state.board_state),
-    }
-}
-
-fn find_best_empty_cell(board_state: BoardState) -> Option<(usize, usize)> {
-    find_winning_cell(board_state)
-        .or_else(|| find_defending_cell(board_state).or_else(|| find_any_empty_cell(board_state)))
-}
-
-fn find_any_empty_cell(board_state: BoardState) -> Option<(usize, usize)> {
-    let available_cells = board_state.cells.iter().enumerate().fold(
-        vec![],
-        |mut acc: Vec<(usize, usize)>, (row, cells)| {
-            cells.iter().enumerate().for_each(|(column, cell)| {
-                match *cell {
-                    BoardCellState::NotSelected(BoardCell::Empty) => acc.push((row, column)),
-                    BoardCellState::Selected(BoardCell::Empty) => acc.push((row, column)),
-                    _ => (),
-                };
-            });
-            acc
-        },
-    );
-
-    if available_cells.len() == 0 {
-        return None;
-    }
-
-    let random_available_cell_index = rand::thread_rng().gen_range(0..available_cells.len());
-
-    Some(available_cells[random_available_cell_index])
-}
-
-fn find_winning_cell(board_state: BoardState) -> Option<(usize, usize)> {
-    let two_in_combination = player_with_two_in_combination(board_state, Player::Computer);
-
-    find_empty_cell_in_combination(board_state, two_in_combination)
-}
-
-fn find_defending_cell(board_state: BoardState) -> Option<(usize, usize)> {
-    let two_in_combination = player_with_two_in_combination(board_state, Player::User);
-
-    find_empty_cell_in_combination(board_state, two_in_combination)
-}
-
-fn player_with_two_in_combination(
-    board_state: BoardState,
-    player: Player,
-) -> Option<&'static [(usize, usize); 3]> {
-    BoardState::winning_combinations()
-        .iter()
-        .find(|combination| {
-            let in_combination = combination.iter().fold(0, |mut count: i32, cell_position| {
-                let (row, column) = cell_position;
-
-                match board_state.cells[*row][*column] {
-                    BoardCellState::Selected(BoardCell::Occupied(cell_player))
-                    | BoardCellState::NotSelected(BoardCell::Occupied(cell_player)) => {
-                        if cell_player == player {
-                            count = count + 1;
-                        } else {
-                            count = count - 1;
-                        }
-                    }
-                    _ => (),
-                }
-                count
-            });
-
-            in_combination == 2
-        })
-}
-
-fn find_empty_cell_in_combination(
-    board_state: BoardState,
-    combination: Option<&[(usize, usize); 3]>,
-) -> Option<(usize, usize)> {
-    if combination.is_none() {
-        return None;
-    }
-
-    let cell_option =
-        combination
-            .unwrap()
-            .iter()
-            .find(|(row, column)| match board_state.cells[*row][*column] {
-                BoardCellState::Selected(BoardCell::Empty)
-                | BoardCellState::NotSelected(BoardCell::Empty) => true,
-                _ => false,
-            });
-
-    if let Some((row, column)) = cell_option {
-        return Some((*row, *column));
-    } else {
-        None
-    }
+    strategy::find_empty_cell_for(
+        &game_state.board_state,
+        Player::Nought,
+        game_state.difficulty_level,
+    )
 }
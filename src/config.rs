@@ -0,0 +1,336 @@
+// Settings for how the game starts, read from a file called `config` next to the bindings file
+// (usually ~/.config/noughts_and_crosses/config). One setting per line, and any of them can be left
+// out:
+//
+//   level hard
+//   opponent dave
+//   first user
+//   think-time 0.5
+//   board 5x5 4
+//   cursor-colour blue
+//   player1-name Alice
+//   player1-token #
+//
+// Every setting can also be given on the command line as `--<setting> <value>`, which wins over the
+// file. `think-time` scales how long the computer waits before moving and chatting, 0 makes it
+// instant. `board` is the rows and columns then the win length, which can be left off to use the
+// shorter side. `level medium:25` picks Medium's mistake rate. Colours are one of the names in
+// parse_colour or #rrggbb. The player names are used in two player games, and the tokens in every
+// game except network ones, where the host is always x. The tokens have to be different.
+use std::{
+    env, fs,
+    io::{self, ErrorKind},
+    path::{Path, PathBuf},
+};
+
+use noughts_and_crosses::{
+    board::{BoardRules, Player},
+    parse::parse_number,
+    strategy::Level,
+};
+use tui::style::Color;
+
+use crate::{app::PlayerDetails, computer::Character};
+
+pub struct Config {
+    // None for Adaptive, which picks its own strength.
+    pub level: Option<Level>,
+    pub opponent: Character,
+    // Who goes first in single games. None picks at random each game.
+    pub first: Option<Player>,
+    pub think_time: f64,
+    pub board: BoardRules,
+    pub colours: Colours,
+    // Player 1 plays as Cross and player 2 as Nought.
+    pub players: [PlayerDetails; 2],
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Colours {
+    pub user: Color,
+    pub computer: Color,
+    pub cursor: Color,
+    pub winning_line: Color,
+}
+
+impl Config {
+    pub fn default() -> Config {
+        Config {
+            level: Some(Level::Easy),
+            opponent: Character::SpeedySteve,
+            first: None,
+            think_time: 1.0,
+            board: BoardRules::CLASSIC,
+            colours: Colours {
+                user: Color::Yellow,
+                computer: Color::Yellow,
+                cursor: Color::Green,
+                winning_line: Color::Magenta,
+            },
+            players: [
+                PlayerDetails {
+                    name: String::from("Player 1"),
+                    token: 'x',
+                },
+                PlayerDetails {
+                    name: String::from("Player 2"),
+                    token: 'o',
+                },
+            ],
+        }
+    }
+
+    // A missing file just means the defaults are used.
+    pub fn load(path: &Path) -> Result<Config, io::Error> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(error) if error.kind() == ErrorKind::NotFound => return Ok(Config::default()),
+            Err(error) => return Err(error),
+        };
+
+        let mut config = Config::default();
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let (setting, value) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+            config.set(setting, value.trim()).map_err(|error| {
+                io::Error::new(
+                    ErrorKind::InvalidData,
+                    format!("line {}: {}", index + 1, error),
+                )
+            })?;
+        }
+
+        Ok(config)
+    }
+
+    // Takes the `--<setting> <value>` pairs out of the arguments and returns the rest.
+    pub fn apply_args(&mut self, args: &[String]) -> Result<Vec<String>, String> {
+        let mut rest = vec![];
+        let mut args = args.iter();
+
+        while let Some(arg) = args.next() {
+            match arg.strip_prefix("--") {
+                Some(setting) => {
+                    let value = args
+                        .next()
+                        .ok_or_else(|| format!("--{} needs a value", setting))?;
+                    self.set(setting, value)
+                        .map_err(|error| format!("--{}: {}", setting, error))?;
+                }
+                None => rest.push(arg.clone()),
+            }
+        }
+
+        Ok(rest)
+    }
+
+    // Settings that only make sense together, checked once the file and the arguments are read.
+    pub fn check(&self) -> Result<(), String> {
+        let [player1, player2] = &self.players;
+        if player1.token == player2.token {
+            return Err(format!(
+                "player1-token and player2-token are both {}",
+                player1.token
+            ));
+        }
+
+        Ok(())
+    }
+
+    // Where the config file goes. None if there's no home directory to put it in.
+    pub fn path() -> Option<PathBuf> {
+        Some(directory()?.join("config"))
+    }
+
+    fn set(&mut self, setting: &str, value: &str) -> Result<(), String> {
+        match setting {
+            "level" => {
+                self.level = match value {
+                    "adaptive" => None,
+                    _ => Some(value.parse()?),
+                }
+            }
+            "opponent" => self.opponent = value.parse()?,
+            "first" => {
+                self.first = match value {
+                    "user" => Some(Player::Cross),
+                    "computer" => Some(Player::Nought),
+                    "random" => None,
+                    _ => return Err(format!("{} is not user, computer or random", value)),
+                }
+            }
+            "think-time" => {
+                self.think_time = match value.parse::<f64>() {
+                    Ok(think_time) if (0.0..=10.0).contains(&think_time) => think_time,
+                    _ => return Err(format!("{} is not a number from 0 to 10", value)),
+                }
+            }
+            "board" => self.board = parse_board(value)?,
+            "user-colour" => self.colours.user = parse_colour(value)?,
+            "computer-colour" => self.colours.computer = parse_colour(value)?,
+            "cursor-colour" => self.colours.cursor = parse_colour(value)?,
+            "win-colour" => self.colours.winning_line = parse_colour(value)?,
+            "player1-name" => self.players[Player::Cross.index()].name = parse_name(value)?,
+            "player2-name" => self.players[Player::Nought.index()].name = parse_name(value)?,
+            "player1-token" => self.players[Player::Cross.index()].token = parse_token(value)?,
+            "player2-token" => self.players[Player::Nought.index()].token = parse_token(value)?,
+            _ => return Err(format!("{} is not a setting", setting)),
+        }
+
+        Ok(())
+    }
+}
+
+// The folder the config and bindings files go in.
+pub fn directory() -> Option<PathBuf> {
+    let config_directory = match env::var_os("XDG_CONFIG_HOME") {
+        Some(directory) if !directory.is_empty() => PathBuf::from(directory),
+        _ => PathBuf::from(env::var_os("HOME")?).join(".config"),
+    };
+
+    Some(config_directory.join("noughts_and_crosses"))
+}
+
+// Where a file the game writes itself goes, like the stats. They're kept with the config so the
+// same ones are used wherever the game is started from, or in the current directory if there's no
+// home directory.
+pub fn file_path(name: &str) -> PathBuf {
+    directory().unwrap_or_default().join(name)
+}
+
+// Makes the folder first, since nothing else might have been saved there yet.
+pub fn write_file(path: &Path, text: String) -> Result<(), io::Error> {
+    if let Some(directory) = path.parent() {
+        fs::create_dir_all(directory)?;
+    }
+    fs::write(path, text)
+}
+
+// `5x5 4`, `5 5 4` or just `5x5`.
+pub fn parse_board(value: &str) -> Result<BoardRules, String> {
+    let numbers = value
+        .split(|character: char| character == 'x' || character.is_whitespace())
+        .filter(|word| !word.is_empty())
+        .map(parse_number)
+        .collect::<Result<Vec<usize>, String>>()?;
+
+    let rules = match numbers.as_slice() {
+        [rows, columns] => BoardRules {
+            rows: *rows,
+            columns: *columns,
+            win_length: *rows.min(columns),
+        },
+        [rows, columns, win_length] => BoardRules {
+            rows: *rows,
+            columns: *columns,
+            win_length: *win_length,
+        },
+        _ => {
+            return Err(format!(
+                "{} is not <rows>x<columns> with an optional win length",
+                value
+            ))
+        }
+    };
+
+    if !rules.is_valid() {
+        return Err(format!("{} isn't a valid board", rules));
+    }
+
+    Ok(rules)
+}
+
+fn parse_name(value: &str) -> Result<String, String> {
+    match value.trim() {
+        "" => Err(String::from("the name is empty")),
+        name => Ok(String::from(name)),
+    }
+}
+
+// Any single character that can be seen, except * which is drawn in empty cells.
+fn parse_token(value: &str) -> Result<char, String> {
+    let mut characters = value.chars();
+    match (characters.next(), characters.next()) {
+        (Some(token), None) if !token.is_whitespace() && !token.is_control() && token != '*' => {
+            Ok(token)
+        }
+        _ => Err(format!("{} is not a single character other than *", value)),
+    }
+}
+
+fn parse_colour(value: &str) -> Result<Color, String> {
+    let colour = match value {
+        "black" => Color::Black,
+        "red" => Color::Red,
+        "green" => Color::Green,
+        "yellow" => Color::Yellow,
+        "blue" => Color::Blue,
+        "magenta" => Color::Magenta,
+        "cyan" => Color::Cyan,
+        "gray" | "grey" => Color::Gray,
+        "dark-gray" | "dark-grey" => Color::DarkGray,
+        "light-red" => Color::LightRed,
+        "light-green" => Color::LightGreen,
+        "light-yellow" => Color::LightYellow,
+        "light-blue" => Color::LightBlue,
+        "light-magenta" => Color::LightMagenta,
+        "light-cyan" => Color::LightCyan,
+        "white" => Color::White,
+        _ => {
+            let hex = value
+                .strip_prefix('#')
+                .filter(|hex| hex.len() == 6)
+                .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                .ok_or_else(|| format!("{} is not a colour name or #rrggbb", value))?;
+            Color::Rgb((hex >> 16) as u8, (hex >> 8) as u8, hex as u8)
+        }
+    };
+
+    Ok(colour)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(words: &[&str]) -> Vec<String> {
+        words.iter().map(|word| String::from(*word)).collect()
+    }
+
+    #[test]
+    fn player_names_and_tokens_can_be_set() {
+        let mut config = Config::default();
+        config
+            .apply_args(&args(&["--player1-name", "Alice", "--player2-token", "#"]))
+            .unwrap();
+
+        assert_eq!(config.players[0].name, "Alice");
+        assert_eq!(config.players[0].token, 'x');
+        assert_eq!(config.players[1].name, "Player 2");
+        assert_eq!(config.players[1].token, '#');
+        assert!(config.check().is_ok());
+    }
+
+    #[test]
+    fn tokens_have_to_be_one_different_character() {
+        for token in ["", "xx", " ", "*"] {
+            assert!(
+                Config::default().set("player1-token", token).is_err(),
+                "{:?} was used",
+                token
+            );
+        }
+        assert!(Config::default().set("player2-name", " ").is_err());
+
+        let mut config = Config::default();
+        config.set("player1-token", "o").unwrap();
+        assert!(config.check().is_err());
+        // Swapping them over is fine once both are set.
+        config.set("player2-token", "x").unwrap();
+        assert!(config.check().is_ok());
+    }
+}
@@ -0,0 +1,147 @@
+// Plays the game over stdin and stdout without the TUI, so that other programs can play against the
+// computer, or against each other using it as the referee. Start it with `engine`, then send one
+// command per line:
+//
+//   position [<rows> <columns> <win length>]   Starts a new game on an empty board. Defaults to 3 3 3.
+//   position <board> <x|o> [<win length>]      Sets up a position, see Position in board.rs.
+//   play <row> <column>                        Places a token for the side to move. 0 0 is the bottom left cell.
+//   genmove                                    The computer picks a cell for the side to move and plays it.
+//   level <easy|medium|hard|impossible|adaptive> [mistake percentage]
+//                                              Sets the strategy genmove uses. Defaults to impossible.
+//                                              Medium can be given a mistake percentage and adaptive
+//                                              has to be.
+//   result                                     Whether the game is still going, who won or if it's a draw.
+//   show                                       The position, in the same form position takes.
+//   quit                                       Exits.
+//
+// x moves first on an empty board. Every command gets a one line reply: `ok`, `move <row> <column>`,
+// `result <none|x|o|draw>`, `position <position>` or `error <reason>`.
+use std::io::{self, BufRead, Write};
+
+use noughts_and_crosses::{
+    board::{BoardRules, PlaceTokenResult, Position},
+    parse::parse_number,
+    strategy::{self, Level},
+};
+
+struct Engine {
+    position: Position,
+    level: Level,
+}
+
+pub fn run() -> io::Result<()> {
+    let mut engine = Engine {
+        position: Position::new(BoardRules::CLASSIC),
+        level: Level::Impossible,
+    };
+    let mut stdout = io::stdout();
+
+    for line in io::stdin().lock().lines() {
+        let line = line?;
+        let words: Vec<&str> = line.split_whitespace().collect();
+
+        if words.first() == Some(&"quit") {
+            break;
+        }
+        if words.is_empty() {
+            continue;
+        }
+
+        let reply = engine
+            .handle(&words)
+            .unwrap_or_else(|error| format!("error {}", error));
+        writeln!(stdout, "{}", reply)?;
+        stdout.flush()?;
+    }
+
+    Ok(())
+}
+
+impl Engine {
+    fn handle(&mut self, words: &[&str]) -> Result<String, String> {
+        match words {
+            ["position"] => self.new_position(BoardRules::CLASSIC),
+            ["position", rows, columns, win_length] if rows.parse::<usize>().is_ok() => self
+                .new_position(BoardRules {
+                    rows: parse_number(rows)?,
+                    columns: parse_number(columns)?,
+                    win_length: parse_number(win_length)?,
+                }),
+            ["position", position @ ..] => {
+                self.position = position.join(" ").parse()?;
+                Ok(String::from("ok"))
+            }
+            ["play", row, column] => {
+                let position = (parse_number(row)?, parse_number(column)?);
+                self.play(position)?;
+                Ok(String::from("ok"))
+            }
+            ["genmove"] => {
+                self.check_in_play()?;
+                let (row, column) = strategy::find_empty_cell_for(
+                    &self.position.board_state,
+                    self.position.to_move,
+                    self.level,
+                )
+                .ok_or("no empty cells")?;
+                self.play((row, column))?;
+                Ok(format!("move {} {}", row, column))
+            }
+            ["level", level] => {
+                self.level = level.parse()?;
+                Ok(String::from("ok"))
+            }
+            ["level", level, mistake_percentage] => {
+                self.level = format!("{}:{}", level, mistake_percentage).parse()?;
+                Ok(String::from("ok"))
+            }
+            ["result"] => {
+                let board_state = &self.position.board_state;
+                let result = match board_state.check_for_winner() {
+                    Some(player) => player.token().to_string(),
+                    None if board_state.is_full() => String::from("draw"),
+                    None => String::from("none"),
+                };
+                Ok(format!("result {}", result))
+            }
+            ["show"] => Ok(format!("position {}", self.position)),
+            [command, ..] => Err(format!("unknown command {}", command)),
+            [] => Err(String::from("no command")),
+        }
+    }
+
+    fn new_position(&mut self, rules: BoardRules) -> Result<String, String> {
+        if !rules.is_valid() {
+            return Err(format!("{} isn't a valid board", rules));
+        }
+
+        self.position = Position::new(rules);
+        Ok(String::from("ok"))
+    }
+
+    fn play(&mut self, position: (usize, usize)) -> Result<(), String> {
+        self.check_in_play()?;
+
+        match self
+            .position
+            .board_state
+            .try_place_token(position, self.position.to_move)
+        {
+            PlaceTokenResult::Success | PlaceTokenResult::SuccessWithWinner(_) => {
+                self.position.to_move = self.position.to_move.opponent();
+                Ok(())
+            }
+            PlaceTokenResult::Occupied(_) => Err(String::from("that cell is already occupied")),
+            PlaceTokenResult::Error => Err(String::from("that cell isn't on the board")),
+        }
+    }
+
+    fn check_in_play(&self) -> Result<(), String> {
+        let board_state = &self.position.board_state;
+        if board_state.check_for_winner().is_some() || board_state.is_full() {
+            return Err(String::from("the game is over"));
+        }
+
+        Ok(())
+    }
+}
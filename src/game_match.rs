@@ -0,0 +1,256 @@
+// Keeps score over a series of games, with whoever didn't start the last game starting the next one.
+// Best of N is N games at most, draws included. It ends early once one side has more wins than the
+// other could still catch up to, and if the wins are level after N games the match is tied. First
+// to N keeps going until someone has N wins, so draws don't count towards anything.
+use std::fmt::{self, Display};
+
+use noughts_and_crosses::board::Player;
+use rand::Rng;
+
+use crate::app::GameResult;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MatchFormat {
+    // Every game stands on its own. This is the default.
+    Single,
+    BestOf(u32),
+    FirstTo(u32),
+}
+
+impl MatchFormat {
+    // The formats that can be picked from the menu, in the order they are cycled through.
+    pub const OPTIONS: [MatchFormat; 6] = [
+        MatchFormat::Single,
+        MatchFormat::BestOf(3),
+        MatchFormat::BestOf(5),
+        MatchFormat::BestOf(7),
+        MatchFormat::FirstTo(3),
+        MatchFormat::FirstTo(5),
+    ];
+
+    pub fn next(&self) -> MatchFormat {
+        let index = MatchFormat::OPTIONS
+            .iter()
+            .position(|format| format == self)
+            .map_or(0, |index| (index + 1) % MatchFormat::OPTIONS.len());
+
+        MatchFormat::OPTIONS[index]
+    }
+}
+
+impl Display for MatchFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MatchFormat::Single => f.write_str("Single games"),
+            MatchFormat::BestOf(games) => write!(f, "Best of {}", games),
+            MatchFormat::FirstTo(wins) => write!(f, "First to {}", wins),
+        }
+    }
+}
+
+// Who is one win away from taking the match.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MatchPoint {
+    User,
+    Computer,
+    // Both sides, so the next game decides it.
+    Both,
+}
+
+pub struct Match {
+    pub format: MatchFormat,
+    // The result of every game so far, in order.
+    pub results: Vec<GameResult>,
+    last_starter: Option<Player>,
+}
+
+impl Match {
+    pub fn default() -> Match {
+        Match {
+            format: MatchFormat::Single,
+            results: vec![],
+            last_starter: None,
+        }
+    }
+
+    pub fn is_on(&self) -> bool {
+        self.format != MatchFormat::Single
+    }
+
+    pub fn reset(&mut self) {
+        self.results.clear();
+        self.last_starter = None;
+    }
+
+    // The first game is a coin flip, then players take turns to start.
+    pub fn next_starter(&mut self) -> Player {
+        let starter = match self.last_starter {
+            Some(player) => player.opponent(),
+            None => [Player::Cross, Player::Nought][rand::thread_rng().gen_range(0..2)],
+        };
+        self.last_starter = Some(starter);
+
+        starter
+    }
+
+    pub fn record(&mut self, result: GameResult) {
+        self.results.push(result);
+    }
+
+    pub fn wins(&self, player: Player) -> u32 {
+        self.results
+            .iter()
+            .filter(|result| {
+                matches!(
+                    (result, player),
+                    (GameResult::UserWon, Player::Cross)
+                        | (GameResult::ComputerWon, Player::Nought)
+                )
+            })
+            .count() as u32
+    }
+
+    pub fn draws(&self) -> u32 {
+        self.results
+            .iter()
+            .filter(|result| matches!(result, GameResult::Draw))
+            .count() as u32
+    }
+
+    pub fn winner(&self) -> Option<Player> {
+        [Player::Cross, Player::Nought]
+            .into_iter()
+            .find(|player| self.has_won(*player, 0))
+    }
+
+    // Over once someone has won, or when every game of a best of has been played even if the match
+    // ended up tied.
+    pub fn is_over(&self) -> bool {
+        match self.format {
+            MatchFormat::Single => false,
+            MatchFormat::BestOf(games) => {
+                self.winner().is_some() || self.results.len() as u32 >= games
+            }
+            MatchFormat::FirstTo(_) => self.winner().is_some(),
+        }
+    }
+
+    pub fn match_point(&self) -> Option<MatchPoint> {
+        if self.is_over() {
+            return None;
+        }
+
+        let on_match_point = |player| self.has_won(player, 1);
+
+        match (
+            on_match_point(Player::Cross),
+            on_match_point(Player::Nought),
+        ) {
+            (true, true) => Some(MatchPoint::Both),
+            (true, false) => Some(MatchPoint::User),
+            (false, true) => Some(MatchPoint::Computer),
+            (false, false) => None,
+        }
+    }
+
+    // Whether the player has won the match, or would have after winning the given number of extra
+    // games.
+    fn has_won(&self, player: Player, extra_wins: u32) -> bool {
+        let wins = self.wins(player) + extra_wins;
+        let played = self.results.len() as u32 + extra_wins;
+
+        match self.format {
+            MatchFormat::Single => false,
+            MatchFormat::BestOf(games) => {
+                wins > self.wins(player.opponent()) + games.saturating_sub(played)
+            }
+            MatchFormat::FirstTo(wins_needed) => wins >= wins_needed,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn match_with(format: MatchFormat, results: &[GameResult]) -> Match {
+        let mut game_match = Match::default();
+        game_match.format = format;
+        for result in results {
+            game_match.record(*result);
+        }
+        game_match
+    }
+
+    #[test]
+    fn best_of_ends_after_its_games_even_with_draws() {
+        let game_match = match_with(MatchFormat::BestOf(3), &[GameResult::Draw; 3]);
+        assert!(game_match.is_over());
+        assert_eq!(game_match.winner(), None);
+    }
+
+    #[test]
+    fn best_of_can_be_tied() {
+        let game_match = match_with(
+            MatchFormat::BestOf(3),
+            &[
+                GameResult::ComputerWon,
+                GameResult::Draw,
+                GameResult::UserWon,
+            ],
+        );
+        assert!(game_match.is_over());
+        assert_eq!(game_match.winner(), None);
+    }
+
+    #[test]
+    fn best_of_ends_once_a_side_cant_be_caught() {
+        let game_match = match_with(
+            MatchFormat::BestOf(3),
+            &[GameResult::UserWon, GameResult::Draw],
+        );
+        assert!(!game_match.is_over());
+        assert_eq!(game_match.match_point(), Some(MatchPoint::User));
+
+        let game_match = match_with(
+            MatchFormat::BestOf(5),
+            &[GameResult::UserWon, GameResult::UserWon, GameResult::Draw],
+        );
+        assert!(!game_match.is_over());
+
+        let game_match = match_with(
+            MatchFormat::BestOf(5),
+            &[
+                GameResult::UserWon,
+                GameResult::UserWon,
+                GameResult::Draw,
+                GameResult::Draw,
+            ],
+        );
+        assert_eq!(game_match.winner(), Some(Player::Cross));
+    }
+
+    #[test]
+    fn first_to_ignores_draws() {
+        let game_match = match_with(
+            MatchFormat::FirstTo(3),
+            &[
+                GameResult::UserWon,
+                GameResult::UserWon,
+                GameResult::Draw,
+                GameResult::Draw,
+            ],
+        );
+        assert!(!game_match.is_over());
+        assert_eq!(game_match.match_point(), Some(MatchPoint::User));
+    }
+
+    #[test]
+    fn both_sides_on_match_point_in_the_last_game() {
+        let game_match = match_with(
+            MatchFormat::BestOf(3),
+            &[GameResult::UserWon, GameResult::ComputerWon],
+        );
+        assert_eq!(game_match.match_point(), Some(MatchPoint::Both));
+    }
+}
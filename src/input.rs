@@ -1,7 +1,14 @@
 use crate::Event;
-use crossterm::event::{read, Event as CrosstermEvent, KeyCode, KeyEvent};
-use std::sync::mpsc::Sender;
+use crossterm::event::{
+    read, Event as CrosstermEvent, KeyCode, KeyEvent, MouseButton, MouseEvent, MouseEventKind,
+};
+use std::{
+    fmt::{self, Display},
+    sync::mpsc::Sender,
+};
 
+// What each key does is worked out from the bindings, see bindings.rs.
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum InputKey {
     Up,
     Down,
@@ -9,78 +16,58 @@ pub enum InputKey {
     Right,
     Enter,
     Esc,
+    Backspace,
     Unhandled,
     Char(char),
+    // A left click at a column and row of the terminal.
+    Click(u16, u16),
 }
 
 pub fn start(sender: Sender<Event>) {
     std::thread::spawn(move || loop {
-        match read().unwrap() {
-            CrosstermEvent::Key(KeyEvent {
-                code: KeyCode::Esc, ..
-            }) => {
-                sender.send(Event::UserInput(InputKey::Esc)).unwrap();
-            }
-            CrosstermEvent::Key(KeyEvent {
-                code: KeyCode::Up, ..
-            }) => {
-                sender.send(Event::UserInput(InputKey::Up)).unwrap();
-            }
-            CrosstermEvent::Key(KeyEvent {
-                code: KeyCode::Down,
-                ..
-            }) => {
-                sender.send(Event::UserInput(InputKey::Down)).unwrap();
-            }
-            CrosstermEvent::Key(KeyEvent {
-                code: KeyCode::Left,
-                ..
-            }) => {
-                sender.send(Event::UserInput(InputKey::Left)).unwrap();
-            }
-            CrosstermEvent::Key(KeyEvent {
-                code: KeyCode::Right,
-                ..
-            }) => {
-                sender.send(Event::UserInput(InputKey::Right)).unwrap();
-            }
-            CrosstermEvent::Key(KeyEvent {
-                code: KeyCode::Enter,
-                ..
-            }) => {
-                sender.send(Event::UserInput(InputKey::Enter)).unwrap();
-            }
-            CrosstermEvent::Key(KeyEvent {
-                code: KeyCode::Char('s'),
-                ..
-            }) => {
-                sender.send(Event::UserInput(InputKey::Char('s'))).unwrap();
-            }
-            CrosstermEvent::Key(KeyEvent {
-                code: KeyCode::Char('n'),
+        let input_key = match read().unwrap() {
+            CrosstermEvent::Key(KeyEvent { code, .. }) => match code {
+                KeyCode::Esc => InputKey::Esc,
+                KeyCode::Up => InputKey::Up,
+                KeyCode::Down => InputKey::Down,
+                KeyCode::Left => InputKey::Left,
+                KeyCode::Right => InputKey::Right,
+                KeyCode::Enter => InputKey::Enter,
+                KeyCode::Backspace => InputKey::Backspace,
+                KeyCode::Char(character) => InputKey::Char(character),
+                _ => InputKey::Unhandled,
+            },
+            CrosstermEvent::Mouse(MouseEvent {
+                kind: MouseEventKind::Down(MouseButton::Left),
+                column,
+                row,
                 ..
-            }) => {
-                sender.send(Event::UserInput(InputKey::Char('n'))).unwrap();
-            }
-            CrosstermEvent::Key(KeyEvent {
-                code: KeyCode::Char('e'),
-                ..
-            }) => {
-                sender.send(Event::UserInput(InputKey::Char('e'))).unwrap();
-            }
-            CrosstermEvent::Key(KeyEvent {
-                code: KeyCode::Char('h'),
-                ..
-            }) => {
-                sender.send(Event::UserInput(InputKey::Char('h'))).unwrap();
-            }
-            CrosstermEvent::Key(KeyEvent {
-                code: KeyCode::Char('c'),
-                ..
-            }) => {
-                sender.send(Event::UserInput(InputKey::Char('c'))).unwrap();
-            }
-            _ => (),
+            }) => InputKey::Click(column, row),
+            _ => InputKey::Unhandled,
+        };
+
+        if input_key != InputKey::Unhandled {
+            sender.send(Event::UserInput(input_key)).unwrap();
         }
     });
 }
+
+impl Display for InputKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InputKey::Up => f.write_str("Up"),
+            InputKey::Down => f.write_str("Down"),
+            InputKey::Left => f.write_str("Left"),
+            InputKey::Right => f.write_str("Right"),
+            InputKey::Enter => f.write_str("Enter"),
+            InputKey::Esc => f.write_str("Esc"),
+            InputKey::Backspace => f.write_str("Backspace"),
+            InputKey::Char(' ') => f.write_str("Space"),
+            InputKey::Char(character) if character.is_uppercase() => {
+                write!(f, "Shift+{}", character)
+            }
+            InputKey::Char(character) => write!(f, "{}", character.to_ascii_uppercase()),
+            InputKey::Click(..) | InputKey::Unhandled => f.write_str("-"),
+        }
+    }
+}
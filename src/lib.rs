@@ -0,0 +1,5 @@
+// The rules of the game and the computer players, without any of the terminal UI. Build with
+// `--no-default-features` to use these without pulling in tui and crossterm.
+pub mod board;
+pub mod parse;
+pub mod strategy;
@@ -1,65 +1,220 @@
+mod adaptive;
 mod app;
+mod bindings;
+mod cell_entry;
+mod cli;
 mod computer;
+mod config;
+mod engine;
+mod game_match;
 mod input;
+mod network;
+mod record;
+mod replay;
+mod selfplay;
+mod stats;
 mod ui;
 
-use std::io;
+use std::{env, io, process};
 
 use crossterm::{
-    event::DisableMouseCapture,
+    event::{DisableMouseCapture, EnableMouseCapture},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, LeaveAlternateScreen},
 };
 
-use app::{App, Level};
+use app::App;
+use bindings::{Bindings, Command};
 use computer::{Action, Trigger};
+use config::Config;
 use input::InputKey;
+use network::{NetworkEvent, Role};
+use noughts_and_crosses::strategy::Level;
+use stats::Stats;
 use std::sync::mpsc::{self, Receiver, Sender};
 use tui::{backend::CrosstermBackend, Terminal};
+use ui::ClickTarget;
 
 pub enum Event {
     UserInput(InputKey),
     ComputerAction(Action),
+    Network(NetworkEvent),
 }
 
-fn main() -> Result<(), io::Error> {
+// The first argument picks what to do, see cli::USAGE. Anything that isn't a command is taken as
+// arguments for play, so `noughts_and_crosses host` still hosts a network game.
+// Errors are printed on their own instead of in debug form, so scripts get a readable message.
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    let result = match args.first().map(String::as_str) {
+        Some("play") => play(&args[1..]),
+        Some("engine") => engine::run(),
+        Some("solve") => cli::solve(&args[1..]),
+        Some("selfplay") => cli::selfplay(&args[1..]),
+        Some("replay") => cli::replay(&args[1..]),
+        Some("stats") => cli::stats(&args[1..]),
+        Some("help" | "--help" | "-h") => {
+            println!("{}", cli::USAGE);
+            Ok(())
+        }
+        _ => play(&args),
+    };
+
+    if let Err(error) = result {
+        eprintln!("error: {}", error);
+        process::exit(1);
+    }
+}
+
+// Run with `host [port]` to host a network game, or `join <address>[:port]` to join one.
+// With no arguments the game is played against the computer.
+fn play(args: &[String]) -> Result<(), io::Error> {
+    let mut app = App::default();
+    if let Some(path) = Bindings::path() {
+        app.set_bindings(Bindings::load(&path).map_err(|error| {
+            io::Error::new(
+                error.kind(),
+                format!("couldn't read {}: {}", path.display(), error),
+            )
+        })?);
+    }
+    // Losing the stats isn't worth refusing to play over, so the game starts without them.
+    let stats_path = Stats::path();
+    match Stats::load(&stats_path) {
+        Ok(stats) => app.stats = stats,
+        Err(error) => {
+            app.instructions = format!(
+                "Couldn't read the stats in {}, so they start again from here: {}. {}",
+                stats_path.display(),
+                error,
+                app.instructions
+            )
+        }
+    }
+    let (sender, receiver): (Sender<Event>, Receiver<Event>) = mpsc::channel();
+
+    let mut config = match Config::path() {
+        Some(path) => Config::load(&path).map_err(|error| {
+            io::Error::new(
+                error.kind(),
+                format!("couldn't read {}: {}", path.display(), error),
+            )
+        })?,
+        None => Config::default(),
+    };
+    let args = config
+        .apply_args(args)
+        .map_err(|error| io::Error::new(io::ErrorKind::InvalidInput, error))?;
+    config
+        .check()
+        .map_err(|error| io::Error::new(io::ErrorKind::InvalidInput, error))?;
+    app.apply_config(&config);
+
+    // Connecting happens before the terminal is taken over so that any errors can be printed.
+    let name = env::var("USER").unwrap_or_else(|_| String::from("Player"));
+    match args
+        .iter()
+        .map(String::as_str)
+        .collect::<Vec<&str>>()
+        .as_slice()
+    {
+        [] => (),
+        ["host"] => {
+            network::host(network::DEFAULT_PORT, sender.clone())?;
+            app.start_network_game(Role::Host, &name);
+        }
+        ["host", port] => {
+            let port = port.parse().map_err(|_| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("{} is not a valid port", port),
+                )
+            })?;
+            network::host(port, sender.clone())?;
+            app.start_network_game(Role::Host, &name);
+        }
+        ["join", address] => {
+            app.start_network_game(Role::Guest, &name);
+            if address.contains(':') {
+                network::join(*address, sender.clone())?;
+            } else {
+                network::join((*address, network::DEFAULT_PORT), sender.clone())?;
+            }
+        }
+        _ => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("unexpected {}\n\n{}", args.join(" "), cli::USAGE),
+            ));
+        }
+    }
+
     enable_raw_mode()?;
-    let stdout = io::stdout();
+    let mut stdout = io::stdout();
+    execute!(stdout, EnableMouseCapture)?;
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
     terminal.clear()?;
 
-    let mut app = App::default();
-
-    let (sender, receiver): (Sender<Event>, Receiver<Event>) = mpsc::channel();
     input::start(sender.clone());
-    let computer_sender = computer::start(sender.clone());
+    let computer_sender = computer::start(sender.clone(), config.think_time);
 
     loop {
+        app.update_analysis();
+        app.update_review();
         terminal.draw(|f| {
             ui::draw(f, &app);
         })?;
 
         match receiver.recv().unwrap() {
-            Event::UserInput(InputKey::Esc) => break,
-            Event::UserInput(input_key) => {
-                handle_user_input(&mut app, input_key, &computer_sender);
+            Event::UserInput(input_key) if app.chat_draft.is_some() => {
+                handle_chat_input(&mut app, input_key);
             }
-            Event::ComputerAction(Action::Chat(words)) => {
-                app.chat = [format!("{}:", app.game_state.computer_character), words].join(" ");
+            Event::UserInput(input_key) if app.cell_draft.is_some() => {
+                handle_cell_input(&mut app, input_key, &computer_sender);
             }
-            Event::ComputerAction(Action::PlaceToken(row, column)) => {
+            Event::UserInput(input_key) if app.replay.is_some() => {
+                handle_replay_input(&mut app, input_key);
+            }
+            Event::UserInput(InputKey::Click(x, y)) => {
+                match ui::click_target(&app, terminal.size()?, x, y) {
+                    Some(ClickTarget::Command(Command::Quit)) => break,
+                    target => handle_click(&mut app, target, &computer_sender),
+                }
+            }
+            Event::UserInput(input_key) => match app.bindings.command(input_key) {
+                Some(Command::Quit) => break,
+                Some(command) => handle_command(&mut app, command, &computer_sender),
+                // Digits that aren't bound to anything pick a cell on the 3x3 board.
+                None => {
+                    if let InputKey::Char(digit @ '1'..='9') = input_key {
+                        app.place_digit(digit as u32 - '0' as u32, &computer_sender);
+                    }
+                }
+            },
+            // Left over from a game that has been reset or a turn that has been undone.
+            Event::ComputerAction(action) if !app.is_current_turn(action.turn_id()) => (),
+            Event::ComputerAction(Action::Chat(_turn_id, words)) => {
+                app.show_chat(format!("{}: {}", app.game_state.computer_character, words));
+            }
+            Event::ComputerAction(Action::PlaceToken(_turn_id, row, column)) => {
                 app.computer_place_token((row, column), &computer_sender);
             }
-            Event::ComputerAction(Action::PlaceTokenError) => {
+            Event::ComputerAction(Action::PlaceTokenError(_turn_id)) => {
                 app.instructions = format!(
                     "Oh no, looks like {} has hit an error when trying to place a token",
                     app.game_state.computer_character
                 )
             }
+            Event::Network(network_event) => {
+                app.handle_network_event(network_event);
+            }
         }
     }
 
+    app.leave_network_game();
+
     disable_raw_mode()?;
     execute!(
         terminal.backend_mut(),
@@ -71,39 +226,154 @@ fn main() -> Result<(), io::Error> {
     Ok(())
 }
 
-fn handle_user_input(app: &mut App, input_key: InputKey, computer_sender: &Sender<Trigger>) {
-    match input_key {
-        InputKey::Up => {
+fn handle_command(app: &mut App, command: Command, computer_sender: &Sender<Trigger>) {
+    match command {
+        Command::Up => {
             app.up();
         }
-        InputKey::Down => {
+        Command::Down => {
             app.down();
         }
-        InputKey::Left => {
+        Command::Left => {
             app.left();
         }
-        InputKey::Right => {
+        Command::Right => {
             app.right();
         }
-        InputKey::Enter => {
+        Command::Place => {
             app.enter(computer_sender);
         }
-        InputKey::Char('s') => {
+        Command::TypeCell => {
+            app.start_cell_entry();
+        }
+        Command::Start => {
             app.start_game(computer_sender);
         }
-        InputKey::Char('n') => {
-            app.new_game();
+        Command::NewGame => {
+            app.new_game(computer_sender);
+        }
+        Command::Undo => {
+            app.undo(computer_sender);
         }
-        InputKey::Char('h') => {
+        Command::Redo => {
+            app.redo(computer_sender);
+        }
+        Command::Hard => {
             app.update_level(Level::Hard);
         }
-        InputKey::Char('e') => {
+        Command::Easy => {
             app.update_level(Level::Easy);
         }
-        InputKey::Char('c') => {
+        Command::Medium => {
+            app.use_medium_level();
+        }
+        Command::MoreMistakes => {
+            app.increase_mistake_percentage();
+        }
+        Command::FewerMistakes => {
+            app.decrease_mistake_percentage();
+        }
+        Command::Impossible => {
+            app.update_level(Level::Impossible);
+        }
+        Command::Adaptive => {
+            app.use_adaptive_level();
+        }
+        Command::ChangeBoard => {
+            app.change_board_rules();
+        }
+        Command::Chat => {
+            app.start_chat();
+        }
+        Command::TwoPlayers => {
+            app.toggle_game_mode();
+        }
+        Command::ChangeOpponent => {
             app.swap_computer_character();
         }
-        InputKey::Unhandled => (),
+        Command::Save => {
+            app.save_game();
+        }
+        Command::Load => {
+            app.load_game(computer_sender);
+        }
+        Command::Watch => {
+            app.start_replay();
+        }
+        Command::MatchFormat => {
+            app.change_match_format();
+        }
+        Command::Hint => {
+            app.hint();
+        }
+        Command::Analysis => {
+            app.toggle_analysis();
+        }
+        Command::JumpToMistake => {
+            app.jump_to_flagged_move();
+        }
+        // Quitting is handled by the main loop.
+        Command::Quit => (),
+    }
+}
+
+// Clicking a cell moves the cursor there and places a token, and clicking a menu entry presses its
+// key.
+fn handle_click(app: &mut App, target: Option<ClickTarget>, computer_sender: &Sender<Trigger>) {
+    match target {
+        Some(ClickTarget::Cell(row, column)) => {
+            app.move_cursor_location(row, column);
+            app.enter(computer_sender);
+        }
+        Some(ClickTarget::Command(command)) => {
+            handle_command(app, command, computer_sender);
+        }
+        None => (),
+    }
+}
+
+fn handle_replay_input(app: &mut App, input_key: InputKey) {
+    match (&mut app.replay, app.bindings.command(input_key)) {
+        (Some(replay), Some(Command::Left)) => replay.back(),
+        (Some(replay), Some(Command::Right)) => replay.forward(),
+        (_, Some(Command::JumpToMistake)) => app.jump_to_flagged_move(),
+        (_, Some(Command::Quit)) => app.replay = None,
+        _ => (),
+    }
+}
+
+fn handle_chat_input(app: &mut App, input_key: InputKey) {
+    match input_key {
+        InputKey::Enter => app.send_chat(),
+        InputKey::Esc => app.chat_draft = None,
+        InputKey::Backspace => {
+            if let Some(draft) = &mut app.chat_draft {
+                draft.pop();
+            }
+        }
+        InputKey::Char(character) => {
+            if let Some(draft) = &mut app.chat_draft {
+                draft.push(character);
+            }
+        }
+        _ => (),
+    }
+}
+
+fn handle_cell_input(app: &mut App, input_key: InputKey, computer_sender: &Sender<Trigger>) {
+    match input_key {
+        InputKey::Enter => app.place_typed_cell(computer_sender),
+        InputKey::Esc => app.cell_draft = None,
+        InputKey::Backspace => {
+            if let Some(draft) = &mut app.cell_draft {
+                draft.pop();
+            }
+        }
+        InputKey::Char(character) => {
+            if let Some(draft) = &mut app.cell_draft {
+                draft.push(character);
+            }
+        }
         _ => (),
     }
 }
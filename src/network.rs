@@ -0,0 +1,259 @@
+// Lets two copies of the game play each other over TCP. One copy hosts a game on a port and the other
+// joins it. Once connected, each side sends messages to the other, one per line:
+//
+//   HELLO <name>                                 Sent by both sides as soon as they connect.
+//   START <rows> <columns> <win length> <first>  Sent by the host to start a game. <first> is HOST or GUEST.
+//   MOVE <row> <column>                          Places a token. Uses the same [row][column] cells as App,
+//                                                with [0][0] being the bottom left cell.
+//   CHAT <text>                                  A chat message. The text runs to the end of the line.
+//   ERROR <reason>                               The last message couldn't be used, usually an illegal move.
+//                                                Moves are placed as soon as they're sent, so a rejected
+//                                                one is taken back.
+//   BYE                                          The other side is leaving.
+//
+// The host always plays x and the guest always plays o. Anything that arrives is passed to the main
+// loop as an Event, the same way the computer's actions are.
+use std::{
+    fmt::{self, Display},
+    io::{self, BufRead, BufReader, Write},
+    net::{TcpListener, TcpStream, ToSocketAddrs},
+    str::FromStr,
+    sync::mpsc::Sender,
+};
+
+use crate::Event;
+use noughts_and_crosses::{board::BoardRules, parse::parse_number};
+
+pub const DEFAULT_PORT: u16 = 4141;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Role {
+    Host,
+    Guest,
+}
+
+impl Role {
+    pub fn token(&self) -> char {
+        match self {
+            Role::Host => 'x',
+            Role::Guest => 'o',
+        }
+    }
+}
+
+impl Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let role = match &self {
+            Role::Host => "HOST",
+            Role::Guest => "GUEST",
+        };
+
+        f.write_str(role)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Message {
+    Hello(String),
+    Start(BoardRules, Role),
+    Move(usize, usize),
+    Chat(String),
+    Error(String),
+    Bye,
+}
+
+impl FromStr for Message {
+    type Err = String;
+
+    fn from_str(line: &str) -> Result<Message, String> {
+        let line = line.trim_end_matches(['\r', '\n']);
+        let (command, rest) = line.split_once(' ').unwrap_or((line, ""));
+        let words: Vec<&str> = rest.split_whitespace().collect();
+
+        match command {
+            "HELLO" => Ok(Message::Hello(String::from(rest))),
+            "START" => match words.as_slice() {
+                [rows, columns, win_length, first] => {
+                    let first = match *first {
+                        "HOST" => Role::Host,
+                        "GUEST" => Role::Guest,
+                        _ => return Err(format!("{} is not HOST or GUEST", first)),
+                    };
+                    let rules = BoardRules {
+                        rows: parse_number(rows)?,
+                        columns: parse_number(columns)?,
+                        win_length: parse_number(win_length)?,
+                    };
+
+                    Ok(Message::Start(rules, first))
+                }
+                _ => Err(String::from(
+                    "START needs rows, columns, a win length and who goes first",
+                )),
+            },
+            "MOVE" => match words.as_slice() {
+                [row, column] => Ok(Message::Move(parse_number(row)?, parse_number(column)?)),
+                _ => Err(String::from("MOVE needs a row and a column")),
+            },
+            "CHAT" => Ok(Message::Chat(String::from(rest))),
+            "ERROR" => Ok(Message::Error(String::from(rest))),
+            "BYE" => Ok(Message::Bye),
+            _ => Err(format!("unknown message {}", command)),
+        }
+    }
+}
+
+impl Display for Message {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Message::Hello(name) => write!(f, "HELLO {}", name),
+            Message::Start(rules, first) => write!(
+                f,
+                "START {} {} {} {}",
+                rules.rows, rules.columns, rules.win_length, first
+            ),
+            Message::Move(row, column) => write!(f, "MOVE {} {}", row, column),
+            // Chat is the only free text that's sent, so line breaks are flattened to keep it on one line.
+            Message::Chat(text) => write!(f, "CHAT {}", text.replace(['\r', '\n'], " ")),
+            Message::Error(reason) => write!(f, "ERROR {}", reason.replace(['\r', '\n'], " ")),
+            Message::Bye => f.write_str("BYE"),
+        }
+    }
+}
+
+// Events sent
+pub enum NetworkEvent {
+    Connected(Peer),
+    Received(Message),
+    // A line from the other side that isn't part of the protocol.
+    Invalid(String),
+    Disconnected,
+}
+
+// The connection to the other player, used to send them messages.
+pub struct Peer {
+    stream: TcpStream,
+}
+
+impl Peer {
+    pub fn send(&mut self, message: &Message) -> io::Result<()> {
+        writeln!(self.stream, "{}", message)
+    }
+}
+
+// Starts listening straight away so that problems like the port being in use are reported before
+// the game starts. The other player can join at any point after that. Only the first connection is
+// taken, so once they leave nobody else can join until the game is hosted again.
+pub fn host(port: u16, sender: Sender<Event>) -> io::Result<()> {
+    let listener = TcpListener::bind(("0.0.0.0", port))?;
+
+    std::thread::spawn(move || match listener.accept() {
+        Ok((stream, _address)) => start(stream, sender),
+        Err(_) => {
+            let _ = sender.send(Event::Network(NetworkEvent::Disconnected));
+        }
+    });
+
+    Ok(())
+}
+
+pub fn join(address: impl ToSocketAddrs, sender: Sender<Event>) -> io::Result<()> {
+    let stream = TcpStream::connect(address)?;
+    start(stream, sender);
+
+    Ok(())
+}
+
+fn start(stream: TcpStream, sender: Sender<Event>) {
+    let reader = match stream.try_clone() {
+        Ok(reader) => BufReader::new(reader),
+        Err(_) => {
+            let _ = sender.send(Event::Network(NetworkEvent::Disconnected));
+            return;
+        }
+    };
+
+    if sender
+        .send(Event::Network(NetworkEvent::Connected(Peer { stream })))
+        .is_err()
+    {
+        return;
+    }
+
+    std::thread::spawn(move || {
+        for line in reader.lines() {
+            let event = match line {
+                Ok(line) => match line.parse::<Message>() {
+                    Ok(message) => NetworkEvent::Received(message),
+                    Err(error) => NetworkEvent::Invalid(error),
+                },
+                Err(_) => break,
+            };
+
+            if sender.send(Event::Network(event)).is_err() {
+                return;
+            }
+        }
+
+        let _ = sender.send(Event::Network(NetworkEvent::Disconnected));
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn messages_round_trip() {
+        let messages = [
+            Message::Hello(String::from("Sam Smith")),
+            Message::Start(
+                BoardRules {
+                    rows: 10,
+                    columns: 12,
+                    win_length: 5,
+                },
+                Role::Guest,
+            ),
+            Message::Start(BoardRules::CLASSIC, Role::Host),
+            Message::Move(0, 2),
+            Message::Chat(String::from("good game")),
+            Message::Error(String::from("it isn't your turn")),
+            Message::Bye,
+        ];
+
+        for message in messages {
+            assert_eq!(message.to_string().parse::<Message>(), Ok(message));
+        }
+    }
+
+    #[test]
+    fn line_breaks_in_chat_are_flattened() {
+        let message = Message::Chat(String::from("one\ntwo\r\nthree"));
+        assert_eq!(message.to_string(), "CHAT one two  three");
+        assert_eq!(
+            "CHAT one two  three\r\n".parse::<Message>(),
+            Ok(Message::Chat(String::from("one two  three")))
+        );
+    }
+
+    #[test]
+    fn badly_formed_messages_are_rejected() {
+        let lines = [
+            "",
+            "HI there",
+            "move 1 2",
+            "MOVE 1",
+            "MOVE 1 2 3",
+            "MOVE one 2",
+            "MOVE -1 2",
+            "START 3 3 3",
+            "START 3 3 3 BOTH",
+            "START 3 x 3 HOST",
+        ];
+
+        for line in lines {
+            assert!(line.parse::<Message>().is_err(), "{}", line);
+        }
+    }
+}
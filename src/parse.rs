@@ -0,0 +1,41 @@
+// Reading numbers out of text. Position strings, levels, saved games, stats, settings and the engine
+// and network messages all use these, so a bad number is reported the same way everywhere.
+use std::str::FromStr;
+
+pub fn parse_number<T: FromStr>(word: &str) -> Result<T, String> {
+    word.parse()
+        .map_err(|_| format!("{} is not a number", word))
+}
+
+// A whole number from 0 to 100, like a mistake rate.
+pub fn parse_percentage(word: &str) -> Result<u8, String> {
+    match word.parse::<u8>() {
+        Ok(percentage) if percentage <= 100 => Ok(percentage),
+        _ => Err(format!("{} is not a percentage", word)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numbers_are_read_as_the_type_asked_for() {
+        assert_eq!(parse_number::<usize>("15"), Ok(15));
+        assert_eq!(parse_number::<u32>("0"), Ok(0));
+        assert!(parse_number::<usize>("-1").is_err());
+        assert_eq!(
+            parse_number::<usize>("x"),
+            Err(String::from("x is not a number"))
+        );
+    }
+
+    #[test]
+    fn percentages_go_up_to_100() {
+        assert_eq!(parse_percentage("0"), Ok(0));
+        assert_eq!(parse_percentage("100"), Ok(100));
+        for word in ["101", "-5", "", "half"] {
+            assert!(parse_percentage(word).is_err(), "{:?} was read", word);
+        }
+    }
+}
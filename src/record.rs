@@ -0,0 +1,328 @@
+// Saves games to a small text file so they can be shared and loaded back in later. A record looks like:
+//
+//   noughts-and-crosses 1
+//   board 3 3 3
+//   mode computer
+//   level medium 40
+//   character steve
+//   first o
+//   moves 1,1 0,0 2,2
+//   chat 1 Steve: Ok, your turn!
+//   result none
+//
+//   board <rows> <columns> <win length>   The size of the board and how many in a row it takes to win.
+//   mode computer|two-player              Network games are saved as two player games.
+//   level easy|hard|impossible|medium <mistake percentage>|adaptive <mistake percentage>
+//   character dave|steve                  Who the computer is playing as.
+//   first x|o                             Who placed the first token. The user always plays x.
+//   moves <row>,<column> ...              Every token placed, in order, starting with the first
+//                                         player. Uses the same [row][column] cells as App, with
+//                                         [0][0] being the bottom left cell.
+//   chat <moves> <text>                   A line from the chat box, and how many moves had been
+//                                         played when it was shown. There's one for each line.
+//   result none|x|o|draw                  none if the game hasn't finished yet.
+//
+// The first line is always the header. The other lines can come in any order, and blank lines and
+// lines starting with # are ignored.
+use std::{
+    fmt::{self, Display},
+    fs,
+    io::{self, ErrorKind},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use noughts_and_crosses::{
+    board::{BoardRules, Player},
+    parse::{parse_number, parse_percentage},
+    strategy::Level,
+};
+
+use crate::{
+    app::{GameMode, GameState},
+    computer::Character,
+    config,
+};
+
+const HEADER: &str = "noughts-and-crosses 1";
+
+#[derive(Clone, Debug)]
+pub struct GameRecord {
+    pub rules: BoardRules,
+    pub mode: GameMode,
+    pub level: Level,
+    pub character: Character,
+    pub first: Player,
+    pub moves: Vec<(usize, usize)>,
+    pub chat: Vec<(usize, String)>,
+    pub result: RecordResult,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RecordResult {
+    Unfinished,
+    Winner(Player),
+    Draw,
+}
+
+impl RecordResult {
+    pub fn of(game_state: &GameState) -> RecordResult {
+        match game_state.winner() {
+            Some(player) => RecordResult::Winner(player),
+            None if game_state.board_state.is_full() => RecordResult::Draw,
+            None => RecordResult::Unfinished,
+        }
+    }
+}
+
+impl GameRecord {
+    pub fn from_game_state(game_state: &GameState) -> GameRecord {
+        GameRecord {
+            rules: game_state.board_state.rules(),
+            mode: match game_state.mode {
+                GameMode::VsComputer => GameMode::VsComputer,
+                _ => GameMode::HotSeat,
+            },
+            level: game_state.difficulty_level,
+            character: game_state.computer_character,
+            first: game_state
+                .moves
+                .first()
+                .map_or(game_state.current_turn, |first_move| first_move.player),
+            moves: game_state
+                .moves
+                .iter()
+                .map(|played_move| (played_move.row, played_move.column))
+                .collect(),
+            chat: game_state.chat.clone(),
+            result: RecordResult::of(game_state),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), io::Error> {
+        config::write_file(path, self.to_string())
+    }
+
+    pub fn load(path: &Path) -> Result<GameRecord, io::Error> {
+        fs::read_to_string(path)?
+            .parse()
+            .map_err(|error| io::Error::new(ErrorKind::InvalidData, error))
+    }
+
+    // Where W saves the game and L and V read it from, next to the config file so it can be loaded
+    // again wherever the game is started from.
+    pub fn path() -> PathBuf {
+        config::file_path("saved_game")
+    }
+}
+
+impl Display for GameRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let moves: Vec<String> = self
+            .moves
+            .iter()
+            .map(|(row, column)| format!("{},{}", row, column))
+            .collect();
+
+        writeln!(f, "{}", HEADER)?;
+        writeln!(
+            f,
+            "board {} {} {}",
+            self.rules.rows, self.rules.columns, self.rules.win_length
+        )?;
+        writeln!(
+            f,
+            "mode {}",
+            match self.mode {
+                GameMode::VsComputer => "computer",
+                _ => "two-player",
+            }
+        )?;
+        match self.level {
+            Level::Easy => writeln!(f, "level easy")?,
+            Level::Medium(mistake_percentage) => {
+                writeln!(f, "level medium {}", mistake_percentage)?
+            }
+            Level::Hard => writeln!(f, "level hard")?,
+            Level::Impossible => writeln!(f, "level impossible")?,
+            Level::Adaptive(mistake_percentage) => {
+                writeln!(f, "level adaptive {}", mistake_percentage)?
+            }
+        }
+        writeln!(f, "character {}", self.character.to_string().to_lowercase())?;
+        writeln!(f, "first {}", self.first.token())?;
+        writeln!(f, "moves {}", moves.join(" "))?;
+        for (turn, text) in &self.chat {
+            writeln!(f, "chat {} {}", turn, text.replace(['\r', '\n'], " "))?;
+        }
+        writeln!(
+            f,
+            "result {}",
+            match self.result {
+                RecordResult::Unfinished => String::from("none"),
+                RecordResult::Winner(player) => player.token().to_string(),
+                RecordResult::Draw => String::from("draw"),
+            }
+        )
+    }
+}
+
+impl FromStr for GameRecord {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<GameRecord, String> {
+        let mut lines = text
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'));
+
+        if lines.next() != Some(HEADER) {
+            return Err(String::from("this isn't a saved game"));
+        }
+
+        // Anything left out falls back to the same settings as a new game.
+        let mut record = GameRecord {
+            rules: BoardRules::CLASSIC,
+            mode: GameMode::VsComputer,
+            level: Level::Easy,
+            character: Character::SpeedySteve,
+            first: Player::Cross,
+            moves: vec![],
+            chat: vec![],
+            result: RecordResult::Unfinished,
+        };
+
+        for line in lines {
+            let (key, rest) = line.split_once(' ').unwrap_or((line, ""));
+            let words: Vec<&str> = rest.split_whitespace().collect();
+
+            match (key, words.as_slice()) {
+                ("board", [rows, columns, win_length]) => {
+                    record.rules = BoardRules {
+                        rows: parse_number(rows)?,
+                        columns: parse_number(columns)?,
+                        win_length: parse_number(win_length)?,
+                    };
+                    if !record.rules.is_valid() {
+                        return Err(format!("{} isn't a valid board", record.rules));
+                    }
+                }
+                ("mode", ["computer"]) => record.mode = GameMode::VsComputer,
+                ("mode", ["two-player"]) => record.mode = GameMode::HotSeat,
+                ("level", ["medium", mistake_percentage]) => {
+                    record.level = Level::Medium(parse_percentage(mistake_percentage)?);
+                }
+                ("level", ["adaptive", mistake_percentage]) => {
+                    record.level = Level::Adaptive(parse_percentage(mistake_percentage)?);
+                }
+                ("level", [level]) => record.level = level.parse()?,
+                ("character", ["dave"]) => record.character = Character::ChattyDave,
+                ("character", ["steve"]) => record.character = Character::SpeedySteve,
+                ("first", [first]) => record.first = parse_player(first)?,
+                ("moves", moves) => {
+                    record.moves = moves
+                        .iter()
+                        .map(|played_move| match played_move.split_once(',') {
+                            Some((row, column)) => Ok((parse_number(row)?, parse_number(column)?)),
+                            None => Err(format!("{} isn't a row,column move", played_move)),
+                        })
+                        .collect::<Result<_, _>>()?;
+                }
+                ("chat", [turn, ..]) => {
+                    // The text is everything after the move count, spaces and all.
+                    let text = rest.trim_start()[turn.len()..].trim_start();
+                    record.chat.push((parse_number(turn)?, String::from(text)));
+                }
+                ("result", ["none"]) => record.result = RecordResult::Unfinished,
+                ("result", ["draw"]) => record.result = RecordResult::Draw,
+                ("result", [winner]) => record.result = RecordResult::Winner(parse_player(winner)?),
+                _ => return Err(format!("couldn't read the line: {}", line)),
+            }
+        }
+
+        Ok(record)
+    }
+}
+
+fn parse_player(word: &str) -> Result<Player, String> {
+    match word {
+        "x" => Ok(Player::Cross),
+        "o" => Ok(Player::Nought),
+        _ => Err(format!("{} is not x or o", word)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAVED: &str = "noughts-and-crosses 1
+board 3 3 3
+mode computer
+level medium 40
+character dave
+first o
+moves 1,1 0,0 2,2
+chat 1 Dave: Ok,  your turn!
+result none
+";
+
+    #[test]
+    fn records_round_trip() {
+        let record: GameRecord = SAVED.parse().unwrap();
+        assert_eq!(record.to_string(), SAVED);
+    }
+
+    #[test]
+    fn every_line_is_read() {
+        let record: GameRecord = SAVED.parse().unwrap();
+        assert_eq!(record.rules, BoardRules::CLASSIC);
+        assert_eq!(record.mode, GameMode::VsComputer);
+        assert_eq!(record.level, Level::Medium(40));
+        assert!(matches!(record.character, Character::ChattyDave));
+        assert_eq!(record.first, Player::Nought);
+        assert_eq!(record.moves, vec![(1, 1), (0, 0), (2, 2)]);
+        // The chat keeps its spacing.
+        assert_eq!(
+            record.chat,
+            vec![(1, String::from("Dave: Ok,  your turn!"))]
+        );
+        assert_eq!(record.result, RecordResult::Unfinished);
+    }
+
+    #[test]
+    fn lines_can_be_left_out_or_reordered() {
+        let record: GameRecord =
+            "# A comment\nnoughts-and-crosses 1\n\nresult x\nlevel adaptive 20\nmode two-player\n"
+                .parse()
+                .unwrap();
+        assert_eq!(record.result, RecordResult::Winner(Player::Cross));
+        assert_eq!(record.level, Level::Adaptive(20));
+        assert_eq!(record.mode, GameMode::HotSeat);
+        assert_eq!(record.rules, BoardRules::CLASSIC);
+        assert!(record.moves.is_empty());
+    }
+
+    #[test]
+    fn bad_records_are_rejected() {
+        for text in [
+            "",
+            "board 3 3 3\n",
+            "noughts-and-crosses 2\n",
+            "noughts-and-crosses 1\nboard 3 3\n",
+            "noughts-and-crosses 1\nboard 3 3 4\n",
+            "noughts-and-crosses 1\nboard 30 30 5\n",
+            "noughts-and-crosses 1\nmode network\n",
+            "noughts-and-crosses 1\nlevel medium 101\n",
+            "noughts-and-crosses 1\nlevel adaptive\n",
+            "noughts-and-crosses 1\nfirst z\n",
+            "noughts-and-crosses 1\nmoves 1,1 2\n",
+            "noughts-and-crosses 1\nmoves 1,a\n",
+            "noughts-and-crosses 1\nchat x hello\n",
+            "noughts-and-crosses 1\nresult maybe\n",
+            "noughts-and-crosses 1\nsomething else\n",
+        ] {
+            assert!(text.parse::<GameRecord>().is_err(), "{:?} was read", text);
+        }
+    }
+}
@@ -0,0 +1,139 @@
+// Steps through a saved game one move at a time, so finished games can be looked back over.
+use noughts_and_crosses::{
+    board::{BoardCell, BoardState, Move, Player},
+    strategy::{self, Annotation},
+};
+
+use crate::{
+    bindings::{Bindings, Command},
+    record::GameRecord,
+};
+
+pub struct Replay {
+    record: GameRecord,
+    // How many of the record's moves are on the board.
+    step: usize,
+    pub board_state: BoardState,
+    // The record's moves marked against the best moves. Empty until the game has been reviewed.
+    review: Vec<Annotation>,
+}
+
+impl Replay {
+    // Starts from the empty board. The record's moves should already have been checked.
+    pub fn new(record: GameRecord) -> Replay {
+        Replay {
+            board_state: BoardState::new(record.rules),
+            record,
+            step: 0,
+            review: vec![],
+        }
+    }
+
+    // For a game that has already been reviewed, so it doesn't have to be worked out again.
+    pub fn with_review(record: GameRecord, review: Vec<Annotation>) -> Replay {
+        Replay {
+            review,
+            ..Replay::new(record)
+        }
+    }
+
+    pub fn review(&self) -> &[Annotation] {
+        &self.review
+    }
+
+    pub fn annotate(&mut self) {
+        let moves: Vec<Move> = self
+            .record
+            .moves
+            .iter()
+            .enumerate()
+            .map(|(index, (row, column))| Move {
+                player: self.player_for(index),
+                row: *row,
+                column: *column,
+                turn: index + 1,
+            })
+            .collect();
+
+        self.review = strategy::annotate_moves(self.record.rules, &moves);
+    }
+
+    pub fn forward(&mut self) {
+        if let Some((row, column)) = self.record.moves.get(self.step).copied() {
+            let player = self.player_for(self.step);
+            self.board_state
+                .set_cell((row, column), BoardCell::Occupied(player));
+            self.step += 1;
+        }
+    }
+
+    pub fn back(&mut self) {
+        if let Some(last_move) = self.last_move() {
+            self.board_state.set_cell(last_move, BoardCell::Empty);
+            self.step -= 1;
+        }
+    }
+
+    pub fn step(&self) -> usize {
+        self.step
+    }
+
+    pub fn go_to(&mut self, step: usize) {
+        while self.step > step {
+            self.back();
+        }
+        while self.step < step.min(self.record.moves.len()) {
+            self.forward();
+        }
+    }
+
+    pub fn last_move(&self) -> Option<(usize, usize)> {
+        self.step
+            .checked_sub(1)
+            .map(|index| self.record.moves[index])
+    }
+
+    // The chat lines that were shown while the board looked like it does now.
+    pub fn chat(&self) -> Vec<&str> {
+        self.record
+            .chat
+            .iter()
+            .filter(|(turn, _)| *turn == self.step)
+            .map(|(_, text)| text.as_str())
+            .collect()
+    }
+
+    pub fn instructions(&self, bindings: &Bindings) -> String {
+        let position = match self.last_move() {
+            Some((row, column)) => format!(
+                "Move {} of {}: {} played {},{}.",
+                self.step,
+                self.record.moves.len(),
+                self.player_for(self.step - 1).token(),
+                row,
+                column
+            ),
+            None => format!(
+                "Start of the game, {} moves to go.",
+                self.record.moves.len()
+            ),
+        };
+
+        format!(
+            "Replay. {} {} and {} to step through the moves, {} to stop watching.",
+            position,
+            bindings.key_name(Command::Left),
+            bindings.key_name(Command::Right),
+            bindings.key_name(Command::Quit)
+        )
+    }
+
+    // Players take turns, starting with whoever went first.
+    fn player_for(&self, index: usize) -> Player {
+        if index.is_multiple_of(2) {
+            self.record.first
+        } else {
+            self.record.first.opponent()
+        }
+    }
+}
@@ -0,0 +1,243 @@
+// Plays two computer strategies against each other as fast as they can go, to find out whether one
+// is really stronger than the other. Moves come straight from the strategy code, so none of the
+// waiting in computer.rs or the terminal UI is involved. Games are split across threads, and the two
+// sides take turns to go first so neither gets the advantage of starting more often.
+use std::{
+    fmt::{self, Display},
+    thread,
+};
+
+use noughts_and_crosses::{
+    board::{BoardCell, BoardRules, BoardState, Player},
+    strategy::{self, Level},
+};
+
+// How many standard deviations either side of the mean covers 95%.
+const Z_95: f64 = 1.96;
+
+// A shorter name than Level's own, to fit in the results table.
+pub fn level_name(level: Level) -> String {
+    match level {
+        Level::Medium(mistake_percentage) => format!("Medium {}%", mistake_percentage),
+        Level::Adaptive(mistake_percentage) => format!("Adaptive {}%", mistake_percentage),
+        level => level.to_string(),
+    }
+}
+
+// Games from one side's point of view.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Tally {
+    pub won: u32,
+    pub drawn: u32,
+    pub lost: u32,
+}
+
+impl Tally {
+    pub fn games(&self) -> u32 {
+        self.won + self.drawn + self.lost
+    }
+
+    fn add(self, other: Tally) -> Tally {
+        Tally {
+            won: self.won + other.won,
+            drawn: self.drawn + other.drawn,
+            lost: self.lost + other.lost,
+        }
+    }
+
+    // A win is worth 1 and a draw a half. The interval treats each game as an independent sample,
+    // which is fine once there are a few hundred games.
+    pub fn score(&self) -> Estimate {
+        let games = self.games() as f64;
+        if games == 0.0 {
+            return Estimate::default();
+        }
+
+        let mean = (self.won as f64 + self.drawn as f64 / 2.0) / games;
+        let variance = (self.won as f64 * (1.0 - mean).powi(2)
+            + self.drawn as f64 * (0.5 - mean).powi(2)
+            + self.lost as f64 * mean.powi(2))
+            / games;
+        let margin = Z_95 * (variance / games).sqrt();
+
+        Estimate {
+            value: mean,
+            low: (mean - margin).max(0.0),
+            high: (mean + margin).min(1.0),
+        }
+    }
+}
+
+// A rate and its 95% confidence interval, all between 0 and 1.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Estimate {
+    pub value: f64,
+    pub low: f64,
+    pub high: f64,
+}
+
+impl Estimate {
+    // Uses the Wilson interval, which behaves itself near 0% and 100% unlike the usual one.
+    pub fn rate(count: u32, games: u32) -> Estimate {
+        if games == 0 {
+            return Estimate::default();
+        }
+
+        let games = games as f64;
+        let rate = count as f64 / games;
+        let z_squared = Z_95 * Z_95;
+        let centre = (rate + z_squared / (2.0 * games)) / (1.0 + z_squared / games);
+        let margin = Z_95 / (1.0 + z_squared / games)
+            * (rate * (1.0 - rate) / games + z_squared / (4.0 * games * games)).sqrt();
+
+        Estimate {
+            value: rate,
+            low: (centre - margin).max(0.0),
+            high: (centre + margin).min(1.0),
+        }
+    }
+}
+
+impl Display for Estimate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:5.1}%  (95% CI {:.1}% to {:.1}%)",
+            self.value * 100.0,
+            self.low * 100.0,
+            self.high * 100.0
+        )
+    }
+}
+
+// Results from the point of view of the first level, split by who went first.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Results {
+    pub going_first: Tally,
+    pub going_second: Tally,
+}
+
+impl Results {
+    pub fn total(&self) -> Tally {
+        self.going_first.add(self.going_second)
+    }
+
+    fn add(self, other: Results) -> Results {
+        Results {
+            going_first: self.going_first.add(other.going_first),
+            going_second: self.going_second.add(other.going_second),
+        }
+    }
+}
+
+// The first level plays x and goes first in the even numbered games.
+pub fn play_games(rules: BoardRules, levels: [Level; 2], games: u32, threads: usize) -> Results {
+    let threads = threads.clamp(1, games.max(1) as usize);
+
+    thread::scope(|scope| {
+        let handles: Vec<_> = (0..threads)
+            .map(|thread| {
+                scope.spawn(move || {
+                    let mut results = Results::default();
+
+                    for game in (thread as u32..games).step_by(threads) {
+                        let first = if game.is_multiple_of(2) {
+                            Player::Cross
+                        } else {
+                            Player::Nought
+                        };
+                        let tally = if first == Player::Cross {
+                            &mut results.going_first
+                        } else {
+                            &mut results.going_second
+                        };
+
+                        match play_game(rules, levels, first) {
+                            Some(Player::Cross) => tally.won += 1,
+                            Some(Player::Nought) => tally.lost += 1,
+                            None => tally.drawn += 1,
+                        }
+                    }
+
+                    results
+                })
+            })
+            .collect();
+
+        handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .fold(Results::default(), Results::add)
+    })
+}
+
+// Returns the winner, or None for a draw. The levels are indexed by Player::index.
+pub fn play_game(rules: BoardRules, levels: [Level; 2], first: Player) -> Option<Player> {
+    let mut board_state = BoardState::new(rules);
+    let mut player = first;
+
+    while let Some(cell) =
+        strategy::find_empty_cell_for(&board_state, player, levels[player.index()])
+    {
+        board_state.set_cell(cell, BoardCell::Occupied(player));
+        if board_state.is_winning_move(cell) {
+            return Some(player);
+        }
+        player = player.opponent();
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-4,
+            "{} is not {}",
+            actual,
+            expected
+        );
+    }
+
+    fn assert_estimate(estimate: Estimate, value: f64, low: f64, high: f64) {
+        assert_close(estimate.value, value);
+        assert_close(estimate.low, low);
+        assert_close(estimate.high, high);
+    }
+
+    // The bounds are the usual published Wilson intervals at 95%.
+    #[test]
+    fn rates_use_the_wilson_interval() {
+        assert_estimate(Estimate::rate(0, 10), 0.0, 0.0, 0.2775);
+        assert_estimate(Estimate::rate(10, 10), 1.0, 0.7225, 1.0);
+        assert_estimate(Estimate::rate(50, 100), 0.5, 0.4038, 0.5962);
+        assert_estimate(Estimate::rate(0, 100), 0.0, 0.0, 0.0370);
+    }
+
+    #[test]
+    fn no_games_give_an_empty_estimate() {
+        assert_estimate(Estimate::rate(0, 0), 0.0, 0.0, 0.0);
+        assert_estimate(Tally::default().score(), 0.0, 0.0, 0.0);
+    }
+
+    #[test]
+    fn draws_count_as_half_a_win() {
+        let all_draws = Tally {
+            won: 0,
+            drawn: 40,
+            lost: 0,
+        };
+        assert_estimate(all_draws.score(), 0.5, 0.5, 0.5);
+
+        // Half wins and half losses also score a half, but with a 1.96 / sqrt(100) margin.
+        let even = Tally {
+            won: 50,
+            drawn: 0,
+            lost: 50,
+        };
+        assert_estimate(even.score(), 0.5, 0.402, 0.598);
+    }
+}
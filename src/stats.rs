@@ -0,0 +1,184 @@
+// Keeps a running total of results against each opponent at each level, saved to a file so it lasts
+// between sessions. One line per opponent and level:
+//
+//   <character> <level> <won> <lost> <drawn> <streak result> <streak length>
+//   dave medium 3 2 1 won 2
+//
+// The streak is the user's current run of the same result, or `none 0` before any games. Adaptive
+// games all count towards one line whatever strength the computer was playing at.
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display},
+    fs,
+    io::{self, ErrorKind},
+    path::{Path, PathBuf},
+};
+
+use noughts_and_crosses::{parse::parse_number, strategy::Level};
+
+use crate::{app::GameResult, computer::Character, config};
+
+pub struct Stats {
+    // Keyed by character then level, the same as they're written in the file.
+    totals: BTreeMap<(String, String), Totals>,
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Totals {
+    pub won: u32,
+    pub lost: u32,
+    pub drawn: u32,
+    pub streak: Option<(GameResult, u32)>,
+}
+
+impl Stats {
+    pub fn default() -> Stats {
+        Stats {
+            totals: BTreeMap::new(),
+        }
+    }
+
+    // A missing file just means no games have been played yet.
+    pub fn load(path: &Path) -> Result<Stats, io::Error> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(error) if error.kind() == ErrorKind::NotFound => return Ok(Stats::default()),
+            Err(error) => return Err(error),
+        };
+
+        let mut stats = Stats::default();
+        for line in text.lines().filter(|line| !line.trim().is_empty()) {
+            let (key, totals) =
+                parse_line(line).map_err(|error| io::Error::new(ErrorKind::InvalidData, error))?;
+            stats.totals.insert(key, totals);
+        }
+
+        Ok(stats)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), io::Error> {
+        config::write_file(path, self.to_string())
+    }
+
+    // Next to the config file, so the stats are the same wherever the game is started from.
+    pub fn path() -> PathBuf {
+        config::file_path("stats")
+    }
+
+    pub fn record(&mut self, character: Character, level: Level, result: GameResult) {
+        let totals = self
+            .totals
+            .entry((character_key(character), level_key(level)))
+            .or_default();
+
+        match result {
+            GameResult::UserWon => totals.won += 1,
+            GameResult::ComputerWon => totals.lost += 1,
+            GameResult::Draw => totals.drawn += 1,
+        }
+        totals.streak = match totals.streak {
+            Some((streak_result, length)) if streak_result == result => Some((result, length + 1)),
+            _ => Some((result, 1)),
+        };
+    }
+
+    // Every opponent and level that has been played, as they're written in the file.
+    pub fn entries(&self) -> impl Iterator<Item = (&str, &str, &Totals)> {
+        self.totals
+            .iter()
+            .map(|((character, level), totals)| (character.as_str(), level.as_str(), totals))
+    }
+
+    pub fn totals(&self, character: Character, level: Level) -> Totals {
+        self.totals
+            .get(&(character_key(character), level_key(level)))
+            .copied()
+            .unwrap_or_default()
+    }
+}
+
+impl Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for ((character, level), totals) in &self.totals {
+            let (streak_result, streak_length) = match totals.streak {
+                Some((result, length)) => (result_key(result), length),
+                None => ("none", 0),
+            };
+            writeln!(
+                f,
+                "{} {} {} {} {} {} {}",
+                character,
+                level,
+                totals.won,
+                totals.lost,
+                totals.drawn,
+                streak_result,
+                streak_length
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Totals {
+    pub fn played(&self) -> u32 {
+        self.won + self.lost + self.drawn
+    }
+
+    // Rounded to the nearest whole percent. None until a game has been played.
+    pub fn win_rate(&self) -> Option<u32> {
+        match self.played() {
+            0 => None,
+            played => Some((self.won * 100 + played / 2) / played),
+        }
+    }
+}
+
+fn parse_line(line: &str) -> Result<((String, String), Totals), String> {
+    let words: Vec<&str> = line.split_whitespace().collect();
+
+    match words.as_slice() {
+        [character, level, won, lost, drawn, streak_result, streak_length] => {
+            let streak_length = parse_number(streak_length)?;
+            let streak = match *streak_result {
+                "none" => None,
+                "won" => Some((GameResult::UserWon, streak_length)),
+                "lost" => Some((GameResult::ComputerWon, streak_length)),
+                "drawn" => Some((GameResult::Draw, streak_length)),
+                _ => return Err(format!("{} is not won, lost, drawn or none", streak_result)),
+            };
+            let totals = Totals {
+                won: parse_number(won)?,
+                lost: parse_number(lost)?,
+                drawn: parse_number(drawn)?,
+                streak,
+            };
+
+            Ok(((String::from(*character), String::from(*level)), totals))
+        }
+        _ => Err(format!("couldn't read the stats line: {}", line)),
+    }
+}
+
+fn character_key(character: Character) -> String {
+    character.to_string().to_lowercase()
+}
+
+// The mistake rate can be changed on Medium and Adaptive's changes from game to game, so it's left
+// out of the key.
+fn level_key(level: Level) -> String {
+    match level {
+        Level::Medium(_) => String::from("medium"),
+        Level::Adaptive(_) => String::from("adaptive"),
+        level => level.to_string().to_lowercase(),
+    }
+}
+
+fn result_key(result: GameResult) -> &'static str {
+    match result {
+        GameResult::UserWon => "won",
+        GameResult::ComputerWon => "lost",
+        GameResult::Draw => "drawn",
+    }
+}
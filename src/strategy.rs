@@ -0,0 +1,707 @@
+// How the computer picks a cell at each level. Nothing here depends on the terminal so it can be
+// used by anything that has a board.
+use crate::board::{BoardCell, BoardRules, BoardState, Move, Player};
+use crate::parse::parse_percentage;
+use rand::Rng;
+use std::{
+    fmt::{self, Display},
+    str::FromStr,
+};
+
+// Larger than anything evaluate can return so a win always beats a good looking position.
+const WINNING_SCORE: i32 = 1_000_000_000;
+// Roughly how many lines the computer is willing to check when picking a move. Each position looked
+// at costs one check per winning line on the board. It's enough to search every game to the end on
+// the classic board.
+const SEARCH_BUDGET: usize = 4_000_000;
+// Boards up to this size have every empty cell searched.
+const SMALL_BOARD_CELLS: usize = 25;
+// How far away from an existing token a cell can be and still be searched on bigger boards.
+const NEARBY_DISTANCE: usize = 2;
+// The mistake rate Medium plays with when it isn't given one.
+pub const DEFAULT_MISTAKE_PERCENTAGE: u8 = 30;
+
+// Medium and Adaptive hold how often, out of 100 moves, they play a random cell instead of the best
+// one. Medium makes its mistakes on top of Hard, and Adaptive on top of Impossible.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Level {
+    Easy,
+    Medium(u8),
+    Hard,
+    Impossible,
+    Adaptive(u8),
+}
+
+impl Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let level = match &self {
+            Level::Easy => String::from("Easy"),
+            Level::Medium(mistake_percentage) => {
+                format!("Medium (strength {}%)", 100 - mistake_percentage)
+            }
+            Level::Hard => String::from("Hard"),
+            Level::Impossible => String::from("Impossible"),
+            Level::Adaptive(mistake_percentage) => {
+                format!("Adaptive (strength {}%)", 100 - mistake_percentage)
+            }
+        };
+
+        f.write_str(&level)
+    }
+}
+
+impl FromStr for Level {
+    type Err = String;
+
+    // The mistake rate goes after a colon, like `medium:25`. Medium can leave it off, but Adaptive
+    // has to have one since it's normally worked out from the results of earlier games.
+    fn from_str(level: &str) -> Result<Level, String> {
+        let lowercase = level.to_lowercase();
+        let (name, mistake_percentage) = match lowercase.split_once(':') {
+            Some((name, percentage)) => (name, Some(parse_percentage(percentage)?)),
+            None => (lowercase.as_str(), None),
+        };
+
+        match (name, mistake_percentage) {
+            ("easy", None) => Ok(Level::Easy),
+            ("medium", percentage) => Ok(Level::Medium(
+                percentage.unwrap_or(DEFAULT_MISTAKE_PERCENTAGE),
+            )),
+            ("hard", None) => Ok(Level::Hard),
+            ("impossible", None) => Ok(Level::Impossible),
+            ("adaptive", Some(percentage)) => Ok(Level::Adaptive(percentage)),
+            ("adaptive", None) => Err(String::from(
+                "adaptive needs a mistake rate, like adaptive:20",
+            )),
+            ("easy" | "hard" | "impossible", Some(_)) => {
+                Err(format!("{} doesn't take a mistake rate", name))
+            }
+            _ => Err(format!(
+                "{} is not easy, medium, hard, impossible or adaptive",
+                level
+            )),
+        }
+    }
+}
+
+// Picks a cell for the player using the strategy for the level.
+pub fn find_empty_cell_for(
+    board_state: &BoardState,
+    player: Player,
+    level: Level,
+) -> Option<(usize, usize)> {
+    match level {
+        Level::Easy => find_any_empty_cell(board_state),
+        Level::Medium(mistake_percentage) => find_empty_cell_with_mistakes(
+            board_state,
+            player,
+            mistake_percentage,
+            find_best_empty_cell,
+        ),
+        Level::Hard => find_best_empty_cell(board_state, player),
+        Level::Impossible => find_strongest_cell(board_state, player),
+        Level::Adaptive(mistake_percentage) => find_empty_cell_with_mistakes(
+            board_state,
+            player,
+            mistake_percentage,
+            find_strongest_cell,
+        ),
+    }
+}
+
+// Plays the given strategy's move most of the time, but every so often blunders into a random cell.
+fn find_empty_cell_with_mistakes(
+    board_state: &BoardState,
+    player: Player,
+    mistake_percentage: u8,
+    find_cell: fn(&BoardState, Player) -> Option<(usize, usize)>,
+) -> Option<(usize, usize)> {
+    if rand::thread_rng().gen_range(0..100) < mistake_percentage {
+        find_any_empty_cell(board_state)
+    } else {
+        find_cell(board_state, player)
+    }
+}
+
+fn find_best_empty_cell(board_state: &BoardState, player: Player) -> Option<(usize, usize)> {
+    find_winning_cell(board_state, player).or_else(|| {
+        find_defending_cell(board_state, player).or_else(|| find_any_empty_cell(board_state))
+    })
+}
+
+fn find_any_empty_cell(board_state: &BoardState) -> Option<(usize, usize)> {
+    let available_cells = board_state.empty_cells();
+
+    if available_cells.is_empty() {
+        return None;
+    }
+
+    let random_available_cell_index = rand::thread_rng().gen_range(0..available_cells.len());
+
+    Some(available_cells[random_available_cell_index])
+}
+
+// Searches ahead from the current board and picks the cell with the best outcome for the player,
+// assuming the other side also plays perfectly. On the classic board every game can be searched to
+// the end so the player never loses. Bigger boards are searched as deep as the budget allows.
+fn find_strongest_cell(board_state: &BoardState, player: Player) -> Option<(usize, usize)> {
+    let mut board_state = board_state.clone();
+    let winning_lines = board_state.winning_lines();
+    let candidates = candidate_cells(&board_state);
+    let max_depth = search_depth(candidates.len(), winning_lines.len());
+
+    let mut best_cell = None;
+    let mut best_score = -i32::MAX;
+
+    for cell in candidates {
+        board_state.set_cell(cell, BoardCell::Occupied(player));
+        let score = -negamax(
+            &mut board_state,
+            &winning_lines,
+            cell,
+            player.opponent(),
+            (1, max_depth),
+            (-i32::MAX, -best_score),
+        );
+        board_state.set_cell(cell, BoardCell::Empty);
+
+        if best_cell.is_none() || score > best_score {
+            best_cell = Some(cell);
+            best_score = score;
+        }
+    }
+
+    best_cell
+}
+
+// What playing in a cell leads to when both sides play perfectly afterwards. Wins and losses count
+// the moves until the game ends, including the one in the cell.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Outcome {
+    Win(u32),
+    Draw,
+    Loss(u32),
+    // The search ran out of budget before finding a result either way.
+    Unknown,
+}
+
+// Works out the outcome of every cell the player could play in. Boards small enough to be searched
+// to the end are solved exactly. On bigger boards only the cells the computer would consider are
+// included, and only forced wins and losses within the search depth are found.
+pub fn solve_cells(board_state: &BoardState, player: Player) -> Vec<((usize, usize), Outcome)> {
+    solve_cells_and(board_state, player, None)
+}
+
+// Like solve_cells, but also solves the extra cell if the computer wouldn't have considered it, to
+// the same depth as the rest.
+fn solve_cells_and(
+    board_state: &BoardState,
+    player: Player,
+    extra_cell: Option<(usize, usize)>,
+) -> Vec<((usize, usize), Outcome)> {
+    let mut board_state = board_state.clone();
+    let winning_lines = board_state.winning_lines();
+    let mut candidates = candidate_cells(&board_state);
+    let max_depth = search_depth(candidates.len(), winning_lines.len());
+    // A draw can only be called when every way the game could go has been looked at.
+    let exhaustive = candidates.len() == board_state.empty_cells().len()
+        && max_depth as usize >= candidates.len();
+
+    if let Some(cell) = extra_cell {
+        if !candidates.contains(&cell) {
+            candidates.push(cell);
+        }
+    }
+
+    candidates
+        .into_iter()
+        .map(|cell| {
+            board_state.set_cell(cell, BoardCell::Occupied(player));
+            // Unlike picking a move, every cell needs its exact score so none can be cut short.
+            let score = -negamax(
+                &mut board_state,
+                &winning_lines,
+                cell,
+                player.opponent(),
+                (1, max_depth),
+                (-i32::MAX, i32::MAX),
+            );
+            board_state.set_cell(cell, BoardCell::Empty);
+
+            (cell, outcome_of(score, exhaustive))
+        })
+        .collect()
+}
+
+// The best outcome the player can get and every cell that gets it. None if there's nowhere to play.
+pub fn best_cells(
+    board_state: &BoardState,
+    player: Player,
+) -> Option<(Outcome, Vec<(usize, usize)>)> {
+    let outcomes = solve_cells(board_state, player);
+    let best = outcomes
+        .iter()
+        .map(|(_, outcome)| *outcome)
+        .max_by_key(|outcome| outcome_rank(*outcome))?;
+
+    let cells = outcomes
+        .into_iter()
+        .filter(|(_, outcome)| *outcome == best)
+        .map(|(cell, _)| cell)
+        .collect();
+
+    Some((best, cells))
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MoveQuality {
+    Best,
+    // Keeps the same result but takes longer to win or loses sooner than it needed to.
+    Inaccuracy,
+    // Changes the result the game should have had, like turning a win into a draw.
+    Blunder,
+}
+
+// How a move compares to the best one that could have been played instead.
+#[derive(Clone, Copy, Debug)]
+pub struct Annotation {
+    pub played_move: Move,
+    pub quality: MoveQuality,
+    pub played: Outcome,
+    pub best: Outcome,
+    pub best_cell: (usize, usize),
+}
+
+// Goes through a finished game and solves the position before every move, for both players.
+pub fn annotate_moves(rules: BoardRules, moves: &[Move]) -> Vec<Annotation> {
+    let mut board_state = BoardState::new(rules);
+
+    moves
+        .iter()
+        .filter_map(|played_move| {
+            let played_cell = (played_move.row, played_move.column);
+            // On bigger boards the move might be somewhere the search wouldn't look, so it's
+            // solved as well rather than being judged without being searched.
+            let outcomes = solve_cells_and(&board_state, played_move.player, Some(played_cell));
+            board_state.set_cell(played_cell, BoardCell::Occupied(played_move.player));
+
+            let (best_cell, best) = outcomes
+                .iter()
+                .copied()
+                .max_by_key(|(_, outcome)| outcome_rank(*outcome))?;
+            let (_, played) = outcomes
+                .iter()
+                .copied()
+                .find(|(cell, _)| *cell == played_cell)?;
+
+            let quality = if result_rank(played) < result_rank(best) {
+                MoveQuality::Blunder
+            } else if outcome_rank(played) < outcome_rank(best) {
+                MoveQuality::Inaccuracy
+            } else {
+                MoveQuality::Best
+            };
+
+            Some(Annotation {
+                played_move: *played_move,
+                quality,
+                played,
+                best,
+                best_cell,
+            })
+        })
+        .collect()
+}
+
+// Wins beat draws beat losses. Unknown is treated as a draw since neither side is known to win.
+fn result_rank(outcome: Outcome) -> i32 {
+    match outcome {
+        Outcome::Win(_) => 1,
+        Outcome::Draw | Outcome::Unknown => 0,
+        Outcome::Loss(_) => -1,
+    }
+}
+
+// Like result_rank, but quicker wins and slower losses are better.
+fn outcome_rank(outcome: Outcome) -> i32 {
+    match outcome {
+        Outcome::Win(moves) => 1000 - moves as i32,
+        Outcome::Draw | Outcome::Unknown => 0,
+        Outcome::Loss(moves) => -1000 + moves as i32,
+    }
+}
+
+// Finished games score within a few hundred of WINNING_SCORE, which evaluate never gets near.
+fn outcome_of(score: i32, exhaustive: bool) -> Outcome {
+    if score > WINNING_SCORE / 2 {
+        Outcome::Win((WINNING_SCORE - score) as u32)
+    } else if score < -WINNING_SCORE / 2 {
+        Outcome::Loss((WINNING_SCORE + score) as u32)
+    } else if exhaustive {
+        Outcome::Draw
+    } else {
+        Outcome::Unknown
+    }
+}
+
+// Scores are from the point of view of the player whose turn it is. A win is worth more the fewer
+// moves it takes, and a loss costs less the longer it can be put off, so the computer will finish
+// games quickly and drag out any it can't save.
+fn negamax(
+    board_state: &mut BoardState,
+    winning_lines: &[Vec<(usize, usize)>],
+    last_move: (usize, usize),
+    player: Player,
+    (depth, max_depth): (i32, i32),
+    (mut alpha, beta): (i32, i32),
+) -> i32 {
+    if board_state.is_winning_move(last_move) {
+        return depth - WINNING_SCORE;
+    }
+
+    let candidates = candidate_cells(board_state);
+
+    // No empty cells left and no winner means the game is a draw.
+    if candidates.is_empty() {
+        return 0;
+    }
+
+    if depth >= max_depth {
+        return evaluate(board_state, winning_lines, player);
+    }
+
+    let mut best_score = -i32::MAX;
+
+    for cell in candidates {
+        board_state.set_cell(cell, BoardCell::Occupied(player));
+        let score = -negamax(
+            board_state,
+            winning_lines,
+            cell,
+            player.opponent(),
+            (depth + 1, max_depth),
+            (-beta, -alpha),
+        );
+        board_state.set_cell(cell, BoardCell::Empty);
+
+        best_score = best_score.max(score);
+        alpha = alpha.max(score);
+
+        // The other player already has a better option earlier in the search, so they'll never
+        // let the game get here.
+        if alpha >= beta {
+            break;
+        }
+    }
+
+    best_score
+}
+
+// Guesses how good an unfinished board is for the player. Every line that only one player has
+// tokens in counts towards them, and more so the closer it is to being complete. Long lines would
+// overflow, so the weights saturate and the total is kept below the scores that mean a forced result.
+fn evaluate(
+    board_state: &BoardState,
+    winning_lines: &[Vec<(usize, usize)>],
+    player: Player,
+) -> i32 {
+    let limit = (WINNING_SCORE / 2 - 1) as i64;
+
+    let total = winning_lines
+        .iter()
+        .map(|line| {
+            let (own, opponent) = line.iter().fold((0, 0), |(own, opponent), position| {
+                match board_state.cell(*position) {
+                    BoardCell::Occupied(cell_player) if cell_player == player => {
+                        (own + 1, opponent)
+                    }
+                    BoardCell::Occupied(_) => (own, opponent + 1),
+                    BoardCell::Empty => (own, opponent),
+                }
+            });
+
+            match (own, opponent) {
+                (0, 0) => 0,
+                (own, 0) => 10_i64.saturating_pow(own),
+                (0, opponent) => -(10_i64.saturating_pow(opponent)),
+                _ => 0,
+            }
+        })
+        .fold(0_i64, i64::saturating_add);
+
+    total.clamp(-limit, limit) as i32
+}
+
+// On bigger boards only cells near existing tokens are worth looking at, otherwise the search
+// spends all of its time on moves in empty corners.
+fn candidate_cells(board_state: &BoardState) -> Vec<(usize, usize)> {
+    let empty_cells = board_state.empty_cells();
+    let BoardRules { rows, columns, .. } = board_state.rules();
+
+    if rows * columns <= SMALL_BOARD_CELLS {
+        return empty_cells;
+    }
+
+    let mut nearby = vec![vec![false; columns]; rows];
+    for (row, column) in board_state.positions() {
+        if let BoardCell::Occupied(_) = board_state.cell((row, column)) {
+            let nearby_rows =
+                row.saturating_sub(NEARBY_DISTANCE)..=(row + NEARBY_DISTANCE).min(rows - 1);
+            for nearby_row in nearby_rows {
+                let nearby_columns = column.saturating_sub(NEARBY_DISTANCE)
+                    ..=(column + NEARBY_DISTANCE).min(columns - 1);
+                for nearby_column in nearby_columns {
+                    nearby[nearby_row][nearby_column] = true;
+                }
+            }
+        }
+    }
+
+    let nearby_cells: Vec<(usize, usize)> = empty_cells
+        .iter()
+        .copied()
+        .filter(|(row, column)| nearby[*row][*column])
+        .collect();
+
+    if nearby_cells.is_empty() {
+        // Nothing has been played yet, so start in the middle.
+        let centre = board_state.centre();
+        if empty_cells.contains(&centre) {
+            return vec![centre];
+        }
+        return empty_cells;
+    }
+
+    nearby_cells
+}
+
+// Picks the deepest search that keeps the number of positions looked at within the budget.
+fn search_depth(candidates: usize, winning_lines: usize) -> i32 {
+    let max_positions = SEARCH_BUDGET / winning_lines.max(1);
+    let mut depth = 0;
+    let mut positions: usize = 1;
+    let mut branches = candidates;
+
+    while branches > 0 && positions * branches <= max_positions {
+        positions *= branches;
+        branches -= 1;
+        depth += 1;
+    }
+
+    depth.max(1)
+}
+
+// A suggested cell and the reason it was picked.
+#[derive(Clone, Copy, Debug)]
+pub struct Hint {
+    pub cell: (usize, usize),
+    pub reason: HintReason,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum HintReason {
+    WinsNow,
+    // Stops the other player completing a line that runs this way.
+    Blocks(LineDirection),
+    // Leaves two cells that would each win, so the other player can only block one of them.
+    CreatesFork,
+    // Nothing as obvious as the others, but it's where the search says to go.
+    Strongest,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LineDirection {
+    Row,
+    Column,
+    Diagonal,
+}
+
+// Picks the cell the computer would play on Impossible for the player, and works out why. Winning
+// and blocking come first so the reason given is the simplest one that applies.
+pub fn find_hint(board_state: &BoardState, player: Player) -> Option<Hint> {
+    if let Some(cell) = find_winning_cell(board_state, player) {
+        return Some(Hint {
+            cell,
+            reason: HintReason::WinsNow,
+        });
+    }
+
+    if let Some(cell) = find_defending_cell(board_state, player) {
+        let direction = match board_state.completed_line(cell, player.opponent()) {
+            Some((0, _)) => LineDirection::Row,
+            Some((_, 0)) => LineDirection::Column,
+            _ => LineDirection::Diagonal,
+        };
+        return Some(Hint {
+            cell,
+            reason: HintReason::Blocks(direction),
+        });
+    }
+
+    let cell = find_strongest_cell(board_state, player)?;
+    let mut board_state = board_state.clone();
+    board_state.set_cell(cell, BoardCell::Occupied(player));
+    let winning_cells = board_state
+        .empty_cells()
+        .into_iter()
+        .filter(|empty_cell| board_state.completes_line(*empty_cell, player))
+        .count();
+
+    Some(Hint {
+        cell,
+        reason: if winning_cells >= 2 {
+            HintReason::CreatesFork
+        } else {
+            HintReason::Strongest
+        },
+    })
+}
+
+fn find_winning_cell(board_state: &BoardState, player: Player) -> Option<(usize, usize)> {
+    find_cell_completing_line(board_state, player)
+}
+
+fn find_defending_cell(board_state: &BoardState, player: Player) -> Option<(usize, usize)> {
+    find_cell_completing_line(board_state, player.opponent())
+}
+
+fn find_cell_completing_line(board_state: &BoardState, player: Player) -> Option<(usize, usize)> {
+    board_state
+        .empty_cells()
+        .into_iter()
+        .find(|cell| board_state.completes_line(*cell, player))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Plays Impossible against every reply the other side could make, to the end of every game.
+    fn assert_never_loses(board_state: &mut BoardState, computer: Player, to_move: Player) {
+        if board_state.check_for_winner().is_some() || board_state.is_full() {
+            assert_ne!(board_state.check_for_winner(), Some(computer.opponent()));
+            return;
+        }
+
+        if to_move == computer {
+            let cell = find_empty_cell_for(board_state, computer, Level::Impossible).unwrap();
+            board_state.set_cell(cell, BoardCell::Occupied(computer));
+            assert_never_loses(board_state, computer, to_move.opponent());
+            board_state.set_cell(cell, BoardCell::Empty);
+        } else {
+            for cell in board_state.empty_cells() {
+                board_state.set_cell(cell, BoardCell::Occupied(to_move));
+                assert_never_loses(board_state, computer, to_move.opponent());
+                board_state.set_cell(cell, BoardCell::Empty);
+            }
+        }
+    }
+
+    #[test]
+    fn impossible_never_loses_on_the_classic_board() {
+        for first in [Player::Cross, Player::Nought] {
+            assert_never_loses(&mut BoardState::default(), Player::Nought, first);
+        }
+    }
+
+    // Nine in a row each on a 20x20 board needing all 20 used to overflow evaluate.
+    #[test]
+    fn long_lines_do_not_overflow() {
+        let rules = BoardRules {
+            rows: 20,
+            columns: 20,
+            win_length: 20,
+        };
+        let mut board_state = BoardState::new(rules);
+        for column in 0..9 {
+            board_state.set_cell((9, column), BoardCell::Occupied(Player::Cross));
+            board_state.set_cell((10, column), BoardCell::Occupied(Player::Nought));
+        }
+
+        let score = evaluate(&board_state, &board_state.winning_lines(), Player::Cross);
+        assert!(score.abs() < WINNING_SCORE / 2);
+
+        assert!(find_empty_cell_for(&board_state, Player::Cross, Level::Impossible).is_some());
+        for (_, outcome) in solve_cells(&board_state, Player::Cross) {
+            assert_eq!(outcome, Outcome::Unknown);
+        }
+    }
+
+    #[test]
+    fn hints_win_before_they_block() {
+        let mut board_state = BoardState::default();
+        board_state.set_cell((0, 0), BoardCell::Occupied(Player::Cross));
+        board_state.set_cell((1, 0), BoardCell::Occupied(Player::Nought));
+        board_state.set_cell((0, 1), BoardCell::Occupied(Player::Cross));
+        board_state.set_cell((1, 1), BoardCell::Occupied(Player::Nought));
+
+        let hint = find_hint(&board_state, Player::Cross).unwrap();
+        assert_eq!(hint.cell, (0, 2));
+        assert_eq!(hint.reason, HintReason::WinsNow);
+
+        // Nought can't win this turn, so its hint is to stop Cross.
+        board_state.set_cell((1, 1), BoardCell::Empty);
+        board_state.set_cell((2, 2), BoardCell::Occupied(Player::Nought));
+        let hint = find_hint(&board_state, Player::Nought).unwrap();
+        assert_eq!(hint.cell, (0, 2));
+        assert_eq!(hint.reason, HintReason::Blocks(LineDirection::Row));
+    }
+
+    // Cross goes first and the players take turns.
+    fn game(cells: &[(usize, usize)]) -> Vec<Move> {
+        let mut player = Player::Cross;
+        cells
+            .iter()
+            .enumerate()
+            .map(|(index, (row, column))| {
+                let played_move = Move {
+                    player,
+                    row: *row,
+                    column: *column,
+                    turn: index + 1,
+                };
+                player = player.opponent();
+                played_move
+            })
+            .collect()
+    }
+
+    #[test]
+    fn annotations_spot_best_moves_and_blunders() {
+        // Answering the centre on an edge lets the cross force a win.
+        let annotations = annotate_moves(BoardRules::CLASSIC, &game(&[(1, 1), (0, 1)]));
+
+        assert_eq!(annotations[0].quality, MoveQuality::Best);
+        assert_eq!(annotations[0].played, Outcome::Draw);
+        assert_eq!(annotations[1].quality, MoveQuality::Blunder);
+        assert_eq!(annotations[1].best, Outcome::Draw);
+        assert!(matches!(annotations[1].played, Outcome::Loss(_)));
+    }
+
+    #[test]
+    fn annotations_spot_slow_wins() {
+        // The cross can win straight away in the top row, but the centre forks and wins a move later.
+        let cells = [(0, 0), (1, 0), (0, 1), (2, 0), (1, 1)];
+        let annotation = annotate_moves(BoardRules::CLASSIC, &game(&cells))[4];
+
+        assert_eq!(annotation.quality, MoveQuality::Inaccuracy);
+        assert_eq!(annotation.best, Outcome::Win(1));
+        assert_eq!(annotation.played, Outcome::Win(3));
+    }
+
+    // Playing far away from everything else still wins, just not straight away.
+    #[test]
+    fn moves_the_search_would_skip_are_still_solved() {
+        let rules = BoardRules {
+            rows: 6,
+            columns: 6,
+            win_length: 4,
+        };
+        let cells = [(2, 1), (0, 0), (2, 2), (0, 2), (2, 3), (0, 4), (5, 5)];
+        let annotation = annotate_moves(rules, &game(&cells))[6];
+
+        assert_eq!(annotation.best, Outcome::Win(1));
+        assert_eq!(annotation.played, Outcome::Win(3));
+        assert_eq!(annotation.quality, MoveQuality::Inaccuracy);
+    }
+}
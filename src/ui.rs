@@ -1,68 +1,337 @@
-use std::io;
 use tui::{
-    backend::CrosstermBackend,
+    backend::Backend,
     layout::{Alignment, Constraint, Direction, Layout, Rect},
-    style::{Color, Style},
-    text::Span,
+    style::{Color, Modifier, Style},
+    text::{Span, Text},
     widgets::{Block, Borders, Cell, List, ListItem, Paragraph, Row, Table},
+    Frame,
 };
 
-use crate::app::{App, BoardCell, BoardCellState, Player};
+use crate::app::{App, GameResult};
+use crate::bindings::Command;
+use crate::config::Colours;
+use crate::game_match::MatchPoint;
+use noughts_and_crosses::{
+    board::{BoardCell, BoardState, Move, Player},
+    strategy::{Level, MoveQuality, Outcome},
+};
+
+// Each menu entry with the commands it lists, shown with whatever keys they're bound to. Related
+// commands share an entry so the whole menu fits on a small terminal.
+const MENU_ENTRIES: [(&str, &[Command]); 12] = [
+    (
+        "Move",
+        &[Command::Up, Command::Down, Command::Left, Command::Right],
+    ),
+    ("Place / Type a cell", &[Command::Place, Command::TypeCell]),
+    ("Start / New game", &[Command::Start, Command::NewGame]),
+    ("Undo / Redo", &[Command::Undo, Command::Redo]),
+    (
+        "Opponent / Board",
+        &[Command::ChangeOpponent, Command::ChangeBoard],
+    ),
+    (
+        "Two players / Match",
+        &[Command::TwoPlayers, Command::MatchFormat],
+    ),
+    (
+        "Save / Load / Watch",
+        &[Command::Save, Command::Load, Command::Watch],
+    ),
+    (
+        "Hint / Analysis / Jump",
+        &[Command::Hint, Command::Analysis, Command::JumpToMistake],
+    ),
+    (
+        "Easy / Medium / Hard",
+        &[Command::Easy, Command::Medium, Command::Hard],
+    ),
+    (
+        "Impossible / Adaptive",
+        &[Command::Impossible, Command::Adaptive],
+    ),
+    (
+        "More / Fewer mistakes",
+        &[Command::MoreMistakes, Command::FewerMistakes],
+    ),
+    ("Chat / Quit", &[Command::Chat, Command::Quit]),
+];
+
+// Space left between the columns of the menu.
+const MENU_COLUMN_GAP: usize = 2;
+
+// Where each part of the screen goes. Worked out the same way for drawing and for mouse clicks.
+struct ScreenAreas {
+    menu: Rect,
+    game: Rect,
+    board: Rect,
+    scoreboard: Rect,
+    stats: Rect,
+    review: Rect,
+    instructions: Rect,
+    chat: Rect,
+}
+
+// What a mouse click landed on.
+pub enum ClickTarget {
+    Cell(usize, usize),
+    // A command in the menu, clicking it is the same as pressing its key.
+    Command(Command),
+}
+
+pub fn draw<B: Backend>(f: &mut Frame<B>, app: &App) {
+    let areas = screen_areas(app, f.size());
+
+    f.render_widget(build_menu_widget(app, areas.menu.width), areas.menu);
+    f.render_widget(build_game_border_widget(), areas.game);
+    f.render_widget(build_scoreboard_widget(app), areas.scoreboard);
+    f.render_widget(build_stats_widget(app), areas.stats);
+    f.render_widget(build_review_widget(app), areas.review);
+    f.render_widget(build_instructions_widget(app), areas.instructions);
+    f.render_widget(build_chat_widget(app), areas.chat);
+
+    // A replay shows its own board, with the last move played highlighted instead of the cursor.
+    let (board_state, highlighted, analysis) = match &app.replay {
+        Some(replay) => (&replay.board_state, replay.last_move(), &[][..]),
+        None => (
+            &app.game_state.board_state,
+            Some((app.cursor_location.row, app.cursor_location.column)),
+            &app.analysis[..],
+        ),
+    };
+
+    // Once a match has been won the board makes way for the final score until N is pressed.
+    if app.game_match.is_over() && app.replay.is_none() {
+        f.render_widget(build_match_summary_widget(app), areas.board);
+        return;
+    }
+
+    let column_widths =
+        vec![Constraint::Length(cell_width(board_state)); board_state.rules().columns];
+    f.render_widget(
+        build_game_table_widget(app, board_state, highlighted, analysis, &column_widths),
+        areas.board,
+    );
+}
+
+// Finds the board cell or menu entry at a point on the screen. The board can only be clicked while
+// it's showing the game being played.
+pub fn click_target(app: &App, screen: Rect, x: u16, y: u16) -> Option<ClickTarget> {
+    let areas = screen_areas(app, screen);
+
+    if contains(areas.board, x, y) && app.replay.is_none() && !app.game_match.is_over() {
+        let rules = app.game_state.board_state.rules();
+        let column = ((x - areas.board.x) / cell_width(&app.game_state.board_state)) as usize;
+        let line = (y - areas.board.y) as usize;
+
+        // Row 0 is drawn at the bottom.
+        if column < rules.columns && line < rules.rows {
+            return Some(ClickTarget::Cell(rules.rows - 1 - line, column));
+        }
+    }
+
+    // Inside the menu's border.
+    if x > areas.menu.x && y > areas.menu.y && contains(areas.menu, x + 1, y + 1) {
+        let layout = MenuLayout::new(app, areas.menu.width);
+        let row = ((y - areas.menu.y - 1) as usize).checked_sub(layout.settings_rows)?;
+        let offset = (x - areas.menu.x - 1) as usize;
+        let column = offset / layout.column_width;
+        if column >= layout.columns {
+            return None;
+        }
+
+        let entry = MENU_ENTRIES.get(row * layout.columns + column)?;
+        let label = menu_label(app, entry);
+        return command_at(&label, entry.1, offset - column * layout.column_width)
+            .map(ClickTarget::Command);
+    }
+
+    None
+}
+
+fn menu_label(app: &App, (names, commands): &(&str, &[Command])) -> String {
+    let keys: Vec<String> = commands
+        .iter()
+        .map(|command| app.bindings.key_name(*command))
+        .collect();
+
+    format!("{} => {}", names, keys.join(" / "))
+}
+
+// Entries like `Undo / Redo => U / R` list more than one command, so the command is picked by which
+// of the names or keys separated by / was clicked.
+fn command_at(label: &str, commands: &[Command], offset: usize) -> Option<Command> {
+    let clicked = label.get(..offset)?;
+    let index = match clicked.split_once(" => ") {
+        Some((_, clicked_keys)) => clicked_keys.matches(" / ").count(),
+        None => clicked.matches(" / ").count(),
+    };
+
+    commands.get(index).copied()
+}
+
+fn contains(area: Rect, x: u16, y: u16) -> bool {
+    x >= area.x && x < area.x + area.width && y >= area.y && y < area.y + area.height
+}
 
-pub fn draw(f: &mut tui::Frame<CrosstermBackend<io::Stdout>>, app: &App) {
+// The menu is as tall as its lines need, which depends on the keys in use and how many columns fit.
+fn screen_areas(app: &App, screen: Rect) -> ScreenAreas {
+    let menu_layout = MenuLayout::new(app, screen.width.saturating_sub(2));
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .margin(1)
         .constraints(
             [
-                Constraint::Percentage(30),
-                Constraint::Percentage(50),
-                Constraint::Percentage(10),
-                Constraint::Percentage(10),
+                Constraint::Length(menu_layout.rows() as u16 + 2),
+                Constraint::Min(0),
+                Constraint::Length(3),
+                Constraint::Length(3),
             ]
             .as_ref(),
         )
-        .split(f.size());
+        .split(screen);
+
+    let game_chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(70), Constraint::Percentage(30)].as_ref())
+        .split(chunks[1]);
+
+    let side_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(
+            [
+                Constraint::Percentage(25),
+                Constraint::Percentage(35),
+                Constraint::Percentage(40),
+            ]
+            .as_ref(),
+        )
+        .split(game_chunks[1]);
+
+    ScreenAreas {
+        menu: chunks[0],
+        game: game_chunks[0],
+        board: generate_game_area(game_chunks[0]),
+        scoreboard: side_chunks[0],
+        stats: side_chunks[1],
+        review: side_chunks[2],
+        instructions: chunks[2],
+        chat: chunks[3],
+    }
+}
 
-    f.render_widget(build_menu_widget(app), chunks[0]);
-    f.render_widget(build_game_border_widget(), chunks[1]);
-    f.render_widget(build_instructions_widget(app), chunks[2]);
-    f.render_widget(build_chat_widget(app), chunks[3]);
-    f.render_widget(build_game_table_widget(app), generate_game_area(chunks[1]));
+// Bigger boards use narrower cells so they still fit on the screen.
+fn cell_width(board_state: &BoardState) -> u16 {
+    if board_state.rules().columns > 5 {
+        3
+    } else {
+        5
+    }
 }
 
-fn build_board_cell(cell: &BoardCell, background_color: Color) -> Cell {
+// Cells in a completed line are picked out so it's clear why the game ended.
+fn build_board_cell(
+    cell: &BoardCell,
+    symbol: String,
+    background_color: Color,
+    width: u16,
+    in_winning_line: bool,
+    colours: &Colours,
+) -> Cell<'static> {
+    let text = format!("{:^1$}", symbol, width as usize);
+
+    if in_winning_line {
+        return Cell::from(text).style(
+            Style::default()
+                .fg(Color::Black)
+                .bg(colours.winning_line)
+                .add_modifier(Modifier::BOLD),
+        );
+    }
+
     match cell {
-        BoardCell::Empty => {
-            Cell::from(format!("{}", cell)).style(Style::default().bg(background_color))
+        BoardCell::Empty => Cell::from(text).style(Style::default().bg(background_color)),
+        BoardCell::Occupied(Player::Cross) => {
+            Cell::from(text).style(Style::default().fg(colours.user).bg(background_color))
+        }
+        BoardCell::Occupied(Player::Nought) => {
+            Cell::from(text).style(Style::default().fg(colours.computer).bg(background_color))
         }
-        BoardCell::Occupied(Player::User) => Cell::from(format!("{}", cell))
-            .style(Style::default().fg(Color::Yellow).bg(background_color)),
-        BoardCell::Occupied(Player::Computer) => Cell::from(format!("{}", cell))
-            .style(Style::default().fg(Color::Yellow).bg(background_color)),
     }
 }
 
-fn build_menu_widget(app: &App) -> List<'_> {
-    let items = [
-        ListItem::new(" "),
-        ListItem::new("Use the below keys to update the game"),
-        ListItem::new(" "),
-        ListItem::new("Start game => S"),
-        ListItem::new("New game => N"),
-        ListItem::new("Change opponent => C"),
-        ListItem::new("Easy => E"),
-        ListItem::new("Hard => H"),
-        ListItem::new(" "),
-        ListItem::new(format!(
-            "Selected level: {}",
-            app.game_state.difficulty_level
-        )),
-        ListItem::new(format!(
-            "Selected opponent: {}",
+fn menu_settings(app: &App) -> [String; 4] {
+    [
+        format!("Level: {}", app.game_state.difficulty_level),
+        format!(
+            "Opponent: {}",
             app.game_state.computer_character.full_name()
-        )),
-    ];
+        ),
+        format!("Mode: {}", app.game_state.mode),
+        format!("Board: {}", app.game_state.board_state.rules()),
+    ]
+}
+
+// The settings and then the entries, each laid out left to right in as many columns as fit across
+// the menu. The entries start on a row of their own.
+struct MenuLayout {
+    columns: usize,
+    // Including the gap after it.
+    column_width: usize,
+    settings_rows: usize,
+    entry_rows: usize,
+}
+
+impl MenuLayout {
+    fn new(app: &App, width: u16) -> MenuLayout {
+        let widest = menu_settings(app)
+            .iter()
+            .cloned()
+            .chain(MENU_ENTRIES.iter().map(|entry| menu_label(app, entry)))
+            .map(|line| line.chars().count())
+            .max()
+            .unwrap_or(0);
+        let column_width = widest + MENU_COLUMN_GAP;
+        // The last column doesn't need a gap after it.
+        let inside = width.saturating_sub(2) as usize + MENU_COLUMN_GAP;
+        let columns = (inside / column_width).max(1);
+
+        MenuLayout {
+            columns,
+            column_width,
+            settings_rows: menu_settings(app).len().div_ceil(columns),
+            entry_rows: MENU_ENTRIES.len().div_ceil(columns),
+        }
+    }
+
+    fn rows(&self) -> usize {
+        self.settings_rows + self.entry_rows
+    }
+
+    fn lines(&self, items: &[String]) -> Vec<String> {
+        items
+            .chunks(self.columns)
+            .map(|row| {
+                row.iter()
+                    .map(|item| format!("{:<1$}", item, self.column_width))
+                    .collect::<String>()
+            })
+            .collect()
+    }
+}
+
+fn build_menu_widget(app: &App, width: u16) -> List<'_> {
+    let layout = MenuLayout::new(app, width);
+    let entries: Vec<String> = MENU_ENTRIES
+        .iter()
+        .map(|entry| menu_label(app, entry))
+        .collect();
+    let items: Vec<ListItem> = layout
+        .lines(&menu_settings(app))
+        .into_iter()
+        .chain(layout.lines(&entries))
+        .map(ListItem::new)
+        .collect();
     List::new(items)
         .block(Block::default().title("Game Options").borders(Borders::ALL))
         .style(Style::default().fg(Color::White))
@@ -70,7 +339,14 @@ fn build_menu_widget(app: &App) -> List<'_> {
 
 fn build_instructions_widget(app: &App) -> Paragraph<'_> {
     let instructions_block = Block::default().title("Instructions").borders(Borders::ALL);
-    let instructions_text = Span::raw(app.instructions.clone());
+    let instructions_text = match (&app.replay, &app.cell_draft) {
+        (Some(replay), _) => Span::raw(replay.instructions(&app.bindings)),
+        (None, Some(draft)) => Span::raw(format!(
+            "Cell, a column letter then a row number like b3 (enter to place, esc to cancel): {}_",
+            draft
+        )),
+        (None, None) => Span::raw(app.instructions.clone()),
+    };
     Paragraph::new(instructions_text)
         .block(instructions_block)
         .alignment(Alignment::Left)
@@ -78,29 +354,286 @@ fn build_instructions_widget(app: &App) -> Paragraph<'_> {
 
 fn build_chat_widget(app: &App) -> Paragraph<'_> {
     let chat_block = Block::default().title("Chat").borders(Borders::ALL);
-    let chat_text = Span::raw(app.chat.clone());
+    let chat_text = match (&app.replay, &app.chat_draft) {
+        (Some(replay), _) => Text::from(replay.chat().join("\n")),
+        (None, Some(draft)) => Text::from(format!(
+            "Message (enter to send, esc to cancel): {}_",
+            draft
+        )),
+        (None, None) => Text::from(app.chat.clone()),
+    };
     Paragraph::new(chat_text)
         .block(chat_block)
         .alignment(Alignment::Left)
 }
 
+fn build_scoreboard_widget(app: &App) -> List<'_> {
+    let game_match = &app.game_match;
+
+    let items = if game_match.is_on() {
+        let mut items = vec![
+            ListItem::new(game_match.format.to_string()),
+            ListItem::new(" "),
+            ListItem::new(format!(
+                "{}: {}",
+                app.game_state.player_name(Player::Cross),
+                game_match.wins(Player::Cross)
+            )),
+            ListItem::new(format!(
+                "{}: {}",
+                app.game_state.player_name(Player::Nought),
+                game_match.wins(Player::Nought)
+            )),
+            ListItem::new(format!("Draws: {}", game_match.draws())),
+        ];
+        let status = match game_match.match_point() {
+            Some(MatchPoint::Both) => String::from("Deciding game!"),
+            Some(MatchPoint::User) => format!(
+                "Match point to {}",
+                app.game_state.player_name(Player::Cross)
+            ),
+            Some(MatchPoint::Computer) => format!(
+                "Match point to {}",
+                app.game_state.player_name(Player::Nought)
+            ),
+            None => String::from(" "),
+        };
+        items.push(ListItem::new(" "));
+        items.push(ListItem::new(status));
+        items
+    } else {
+        vec![ListItem::new(format!(
+            "Single games. Press {} to play a match.",
+            app.bindings.key_name(Command::MatchFormat)
+        ))]
+    };
+
+    List::new(items)
+        .block(Block::default().title("Match").borders(Borders::ALL))
+        .style(Style::default().fg(Color::White))
+}
+
+fn build_match_summary_widget(app: &App) -> Paragraph<'_> {
+    let game_match = &app.game_match;
+    let format = game_match.format.to_string().to_lowercase();
+    let result = match game_match.winner() {
+        Some(winner) => format!(
+            "{} won the match {}-{} ({})",
+            app.game_state.player_name(winner),
+            game_match.wins(winner),
+            game_match.wins(winner.opponent()),
+            format
+        ),
+        None => format!(
+            "The match was tied {}-{} ({})",
+            game_match.wins(Player::Cross),
+            game_match.wins(Player::Nought),
+            format
+        ),
+    };
+
+    let mut lines = vec![
+        String::from("Match over"),
+        String::new(),
+        result,
+        String::new(),
+    ];
+    for (game, result) in game_match.results.iter().enumerate() {
+        let result = match result {
+            GameResult::UserWon => format!("{} won", app.game_state.player_name(Player::Cross)),
+            GameResult::ComputerWon => {
+                format!("{} won", app.game_state.player_name(Player::Nought))
+            }
+            GameResult::Draw => String::from("Draw"),
+        };
+        lines.push(format!("Game {}: {}", game + 1, result));
+    }
+    lines.push(String::new());
+    lines.push(format!(
+        "Press {} to start a new match.",
+        app.bindings.key_name(Command::NewGame)
+    ));
+
+    Paragraph::new(Text::from(lines.join("\n"))).alignment(Alignment::Left)
+}
+
+// Every move of the finished game, or of the saved game being watched, with how it compares to the
+// best move. The move being looked at in a replay is marked.
+fn build_review_widget(app: &App) -> List<'_> {
+    let current_step = app.replay.as_ref().map(|replay| replay.step());
+    let outcome_name = |outcome| match outcome {
+        Outcome::Win(_) => "win",
+        Outcome::Draw => "draw",
+        Outcome::Loss(_) => "loss",
+        Outcome::Unknown => "unclear",
+    };
+
+    let items: Vec<ListItem> = if app.shown_review().is_empty() {
+        vec![ListItem::new(
+            "Finish a game to see a review of every move.",
+        )]
+    } else {
+        app.shown_review()
+            .iter()
+            .map(|annotation| {
+                let Move {
+                    player,
+                    row,
+                    column,
+                    turn,
+                } = annotation.played_move;
+                let (best_row, best_column) = annotation.best_cell;
+                let quality = match annotation.quality {
+                    MoveQuality::Best => String::from("best"),
+                    MoveQuality::Inaccuracy => {
+                        format!("inaccuracy, best {},{}", best_row, best_column)
+                    }
+                    MoveQuality::Blunder => format!(
+                        "blunder, {} to {}, best {},{}",
+                        outcome_name(annotation.best),
+                        outcome_name(annotation.played),
+                        best_row,
+                        best_column
+                    ),
+                };
+                let marker = if current_step == Some(turn) { ">" } else { " " };
+                let style = match annotation.quality {
+                    MoveQuality::Best => Style::default(),
+                    MoveQuality::Inaccuracy => Style::default().fg(Color::Yellow),
+                    MoveQuality::Blunder => Style::default().fg(Color::Red),
+                };
+
+                ListItem::new(format!(
+                    "{}{}. {} {},{} {}",
+                    marker,
+                    turn,
+                    app.game_state.cell_symbol(BoardCell::Occupied(player)),
+                    row,
+                    column,
+                    quality
+                ))
+                .style(style)
+            })
+            .collect()
+    };
+
+    List::new(items)
+        .block(
+            Block::default()
+                .title(format!(
+                    "Review ({} to jump to mistakes)",
+                    app.bindings.key_name(Command::JumpToMistake)
+                ))
+                .borders(Borders::ALL),
+        )
+        .style(Style::default().fg(Color::White))
+}
+
+// Totals for the selected opponent and level, kept between sessions.
+fn build_stats_widget(app: &App) -> List<'_> {
+    let totals = app.stats.totals(
+        app.game_state.computer_character,
+        app.game_state.difficulty_level,
+    );
+    let level = match app.game_state.difficulty_level {
+        Level::Medium(_) => String::from("Medium"),
+        Level::Adaptive(_) => String::from("Adaptive"),
+        level => level.to_string(),
+    };
+    let win_rate = match totals.win_rate() {
+        Some(win_rate) => format!("{}%", win_rate),
+        None => String::from("-"),
+    };
+    let streak = match totals.streak {
+        Some((GameResult::UserWon, length)) => format!("{} won", length),
+        Some((GameResult::ComputerWon, length)) => format!("{} lost", length),
+        Some((GameResult::Draw, length)) => format!("{} drawn", length),
+        None => String::from("-"),
+    };
+
+    let items = [
+        ListItem::new(format!(
+            "Against {} on {}",
+            app.game_state.computer_character, level
+        )),
+        ListItem::new(" "),
+        ListItem::new(format!("Played: {}", totals.played())),
+        ListItem::new(format!("Won: {}", totals.won)),
+        ListItem::new(format!("Lost: {}", totals.lost)),
+        ListItem::new(format!("Drawn: {}", totals.drawn)),
+        ListItem::new(format!("Win rate: {}", win_rate)),
+        ListItem::new(format!("Current streak: {}", streak)),
+    ];
+    List::new(items)
+        .block(Block::default().title("Stats").borders(Borders::ALL))
+        .style(Style::default().fg(Color::White))
+}
+
 fn build_game_border_widget() -> Block<'static> {
     Block::default().title("Game").borders(Borders::ALL)
 }
 
-fn build_game_table_widget(app: &App) -> Table<'_> {
-    Table::new(app.game_state.board_state.cells.iter().rev().map(|c| {
-        Row::new(c.iter().map(|cell_with_state| match cell_with_state {
-            BoardCellState::Selected(cell) => build_board_cell(&cell, Color::Green),
-            BoardCellState::NotSelected(cell) => build_board_cell(&cell, Color::Reset),
+// Cells in the analysis are coloured by their outcome and show how many moves it takes to get there.
+// The highlighted cell keeps its usual colour so the cursor can still be seen.
+fn build_game_table_widget<'a>(
+    app: &'a App,
+    board_state: &'a BoardState,
+    highlighted: Option<(usize, usize)>,
+    analysis: &'a [((usize, usize), Outcome)],
+    column_widths: &'a [Constraint],
+) -> Table<'a> {
+    let width = cell_width(board_state);
+    let winning_cells: Vec<(usize, usize)> = board_state
+        .completed_lines()
+        .into_iter()
+        .flat_map(|(_, line)| line)
+        .collect();
+    let winning_cells = &winning_cells;
+
+    // Row 0 is the bottom of the board, so the rows are drawn in reverse.
+    let rows = board_state.cells().iter().enumerate().rev();
+
+    Table::new(rows.map(move |(row, cells)| {
+        Row::new(cells.iter().enumerate().map(move |(column, cell)| {
+            let outcome = analysis
+                .iter()
+                .find(|(cell, _)| *cell == (row, column))
+                .map(|(_, outcome)| *outcome);
+
+            if let Some(outcome) = outcome {
+                let (symbol, analysis_color) = match outcome {
+                    Outcome::Win(moves) => (format!("W{}", moves), Color::Blue),
+                    Outcome::Draw => (String::from("D"), Color::Yellow),
+                    Outcome::Loss(moves) => (format!("L{}", moves), Color::Red),
+                    Outcome::Unknown => (String::from("?"), Color::DarkGray),
+                };
+                let background = if highlighted == Some((row, column)) {
+                    app.colours.cursor
+                } else {
+                    analysis_color
+                };
+                let text = format!("{:^1$}", symbol, width as usize);
+                return Cell::from(text).style(Style::default().fg(Color::Black).bg(background));
+            }
+
+            let background = if highlighted == Some((row, column)) {
+                app.colours.cursor
+            } else {
+                Color::Reset
+            };
+
+            build_board_cell(
+                cell,
+                app.game_state.cell_symbol(*cell),
+                background,
+                width,
+                winning_cells.contains(&(row, column)),
+                &app.colours,
+            )
         }))
     }))
     .style(Style::default().fg(Color::White))
-    .widths(&[
-        Constraint::Length(5),
-        Constraint::Length(5),
-        Constraint::Length(5),
-    ])
+    .widths(column_widths)
     .column_spacing(0)
 }
 
@@ -117,3 +650,59 @@ fn generate_game_area(game_block: Rect) -> Rect {
 
     v[1]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tui::{backend::TestBackend, Terminal};
+
+    fn render(app: &App, width: u16, height: u16) -> Vec<String> {
+        let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+        terminal.draw(|f| draw(f, app)).unwrap();
+        let buffer = terminal.backend().buffer();
+        (0..height)
+            .map(|y| {
+                (0..width)
+                    .map(|x| buffer.get(x, y).symbol.as_str())
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn the_whole_menu_fits_on_a_small_terminal() {
+        let app = App::default();
+        let screen = render(&app, 80, 24);
+        let lines = menu_settings(&app)
+            .into_iter()
+            .chain(MENU_ENTRIES.iter().map(|entry| menu_label(&app, entry)));
+
+        for line in lines {
+            assert!(
+                screen.iter().any(|row| row.contains(&line)),
+                "{} is cut off",
+                line
+            );
+        }
+    }
+
+    #[test]
+    fn the_last_menu_entry_can_be_clicked() {
+        let app = App::default();
+        let screen = render(&app, 80, 24);
+        let last = menu_label(&app, &MENU_ENTRIES[MENU_ENTRIES.len() - 1]);
+        let (y, row) = screen
+            .iter()
+            .enumerate()
+            .find(|(_, row)| row.contains(&last))
+            .unwrap();
+        let x = row.find("Esc").unwrap();
+        // The borders are wider than one byte each.
+        let x = row[..x].chars().count();
+
+        assert!(matches!(
+            click_target(&app, Rect::new(0, 0, 80, 24), x as u16, y as u16),
+            Some(ClickTarget::Command(Command::Quit))
+        ));
+    }
+}